@@ -0,0 +1,21 @@
+//! Measures clearing the screen with `00E0`, the most common full screen operation.
+use std::hint::black_box;
+
+use choccy_chip::prelude::*;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+fn clear_screen(c: &mut Criterion) {
+    let mut emu = Emu::new();
+    // clear the screen
+    emu.load_rom(&[0x00, 0xE0]).unwrap();
+
+    c.bench_function("clear_screen", |b| {
+        b.iter(|| {
+            emu.set_start_address(0x200);
+            black_box(emu.cycle()).unwrap();
+        });
+    });
+}
+
+criterion_group!(benches, clear_screen);
+criterion_main!(benches);
@@ -1,10 +1,24 @@
 //! The Emu struct is used to emulate the CHIP-8 CPU.
+use core::fmt;
+use std::collections::{HashSet, VecDeque};
+use std::fmt::{Display, Write};
+use std::iter::Enumerate;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use super::input::InputEvent;
+use super::opcode::{OpCode, OpCodeError};
+use super::quirks::{JumpQuirk, MemoryQuirk, Quirks, QuirksError, ShiftQuirk};
+use super::rom_parser::{detect_variant, Chip8Variant, ValidRom};
 use super::{
-    registers, input, NUM_KEYS, RAM_SIZE, SCREEN_HEIGHT, SCREEN_WIDTH, SPRITE_SET, SPRITE_SET_SIZE,
-    STACK_SIZE,
+    registers, input, LARGE_SPRITE_SET, LARGE_SPRITE_SET_ADDRESS, LARGE_SPRITE_SET_SIZE, NUM_KEYS,
+    OPCODE_SIZE, PROGRAM_START, RAM_SIZE, SCREEN_HEIGHT, SCREEN_WIDTH, SPRITE_SET, SPRITE_SET_SIZE, STACK_SIZE,
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 /// The Emu struct is used to emulate the CHIP-8 CPU.
 // TODO: consider whether this should be in topmost lib.rs and how API should be structured
 pub struct Emu {
@@ -26,23 +40,306 @@ pub struct Emu {
     pub(crate) keys: [bool; NUM_KEYS],
     /// The screen is used to store the state of the CHIP-8 screen.
     pub(crate) screen: [bool; SCREEN_WIDTH * SCREEN_HEIGHT],
+    /// Whether the screen changed since the frontend last checked, see [`Emu::take_dirty`].
+    pub(crate) dirty: bool,
     /// The input struct is used to map keyboard inputs to CHIP-8 keys.
     pub(crate) keymapping: input::Input,
+    /// Problems noticed that are not errors, waiting to be taken by the frontend.
+    pub(crate) warnings: Vec<EmuWarning>,
+    /// The last ROM loaded, kept so [`Emu::reset_preserving_rom`] can load it again.
+    pub(crate) rom: Vec<u8>,
+    /// The address the last ROM was loaded at.
+    pub(crate) rom_start: u16,
+    /// The address programs start at, see [`Emu::set_start_address`].
+    pub(crate) start_address: u16,
+    /// The interpreter behaviours to follow.
+    pub(crate) quirks: Quirks,
+    /// Whether execution is stopped until the next frame, see [`Quirks::display_wait`].
+    pub(crate) waiting_for_frame: bool,
+    /// Whether the program is stopped at an `FX0A` until a key is pressed and released.
+    pub(crate) awaiting_key: bool,
+    /// The key released while stopped at an `FX0A`, waiting to be stored by it.
+    pub(crate) key_released: Option<usize>,
+    /// How sprites are drawn, see [`DrawMode`].
+    pub(crate) draw_mode: DrawMode,
+    /// What to do with opcodes that are not CHIP-8 instructions.
+    pub(crate) unknown_opcode_policy: UnknownOpCodePolicy,
+    /// When the timers were last brought up to date by [`Emu::update_timers`].
+    pub(crate) last_timer_update: Option<Instant>,
+    /// How many frames have passed since the emulator started, see [`Emu::frame`].
+    pub(crate) frame: u64,
+    /// The key presses and releases since [`Emu::start_recording`], with the frame they happened on.
+    pub(crate) recording: Option<Vec<(u64, InputEvent)>>,
+    /// Recorded key presses and releases waiting to be replayed, see [`Emu::play_recording`].
+    pub(crate) playback: VecDeque<(u64, InputEvent)>,
+    /// How many subroutine calls can be nested, see [`Emu::set_max_stack_depth`].
+    pub(crate) max_stack_depth: u8,
+    /// The character set loaded into memory, see [`EmuBuilder::font`].
+    pub(crate) font: [u8; SPRITE_SET_SIZE],
+    /// How many instructions have run since the emulator started, see [`Emu::total_cycles`].
+    pub(crate) total_cycles: u64,
+    /// Where `CXNN` gets its random numbers, see [`Emu::set_rng_seed`].
+    pub(crate) rng: StdRng,
+    /// The raw opcodes that could not be decoded, see [`Emu::unknown_opcodes_seen`].
+    pub(crate) unknown_opcodes: HashSet<u16>,
+    /// The RAM addresses a debugger is watching, see [`Emu::add_watch`].
+    pub(crate) watches: HashSet<u16>,
+    /// The watched addresses that changed, waiting to be taken by the frontend.
+    pub(crate) watch_hits: Vec<u16>,
+    /// Plays the beep while the sound timer is running, only built with the `sound` feature.
+    #[cfg(feature = "sound")]
+    pub(crate) audio: super::sound::Audio,
+}
+
+/// The `EmuError` enum represents the different errors that can occur while running the emulator.
+#[derive(Debug, PartialEq)]
+pub enum EmuError {
+    /// The ROM does not fit in memory.
+    RomLoadError,
+    /// Executing an opcode failed.
+    OpCodeError(OpCodeError),
+    /// A font is not [`SPRITE_SET_SIZE`] bytes long. Holds the length it was.
+    InvalidFont(usize),
+    /// The quirks contradict each other, see [`Quirks::validate`].
+    InvalidQuirks(QuirksError),
+}
+
+impl Display for EmuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EmuError::RomLoadError => write!(f, "ROM is too large to fit in memory"),
+            EmuError::OpCodeError(err) => write!(f, "{err}"),
+            EmuError::InvalidFont(len) => {
+                write!(f, "font is {len} bytes long, expected {SPRITE_SET_SIZE}")
+            }
+            EmuError::InvalidQuirks(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for EmuError {}
+
+impl From<OpCodeError> for EmuError {
+    fn from(err: OpCodeError) -> Self {
+        EmuError::OpCodeError(err)
+    }
+}
+
+/// The `CycleOutcome` enum represents what happened during a call to [`Emu::cycle`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CycleOutcome {
+    /// An instruction was executed.
+    Executed,
+    /// No instruction was executed because the emulator is waiting for the next frame,
+    /// see [`Quirks::display_wait`]. Frontends should move on to the next frame rather than
+    /// calling [`Emu::cycle`] again.
+    WaitingForFrame,
+    /// The program is waiting for a key press, see [`Emu::awaiting_key`]. Cycling again
+    /// does nothing until a key is released, so frontends can wait for input instead.
+    WaitingForKey,
+    /// The instruction was a jump to itself, which is how CHIP-8 programs end or idle.
+    /// Nothing changes until a timer or key does, so frontends can end the frame early.
+    Halted,
+}
+
+/// The `BatchResult` struct reports how a call to [`Emu::cycle_batch`] went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchResult {
+    /// How many instructions were executed.
+    pub cycles: usize,
+    /// Why the batch stopped early, or `None` if every cycle ran.
+    /// This is never [`CycleOutcome::Executed`].
+    pub stopped: Option<CycleOutcome>,
+}
+
+/// The `ScreenView` struct borrows the screen for custom renderers, see [`Emu::screen_view`].
+/// Iterating over it yields every pixel as `(x, y, on)`, row by row from the top left.
+#[derive(Debug, Clone, Copy)]
+pub struct ScreenView<'a> {
+    pixels: &'a [bool],
+    width: usize,
+    height: usize,
+}
+
+impl ScreenView<'_> {
+    #[must_use]
+    /// Returns the width of the screen in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[must_use]
+    /// Returns the height of the screen in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+}
+
+impl<'a> IntoIterator for ScreenView<'a> {
+    type Item = (usize, usize, bool);
+    type IntoIter = ScreenViewIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        ScreenViewIter {
+            pixels: self.pixels.iter().enumerate(),
+            width: self.width,
+        }
+    }
+}
+
+/// The `ScreenViewIter` struct iterates over the pixels of a [`ScreenView`] as `(x, y, on)`.
+#[derive(Debug, Clone)]
+pub struct ScreenViewIter<'a> {
+    pixels: Enumerate<std::slice::Iter<'a, bool>>,
+    width: usize,
 }
 
-// pub enum EmuError {
-//     RomLoadError,
-//     OpCodeError,
-//     OtherError,
-// }
+impl Iterator for ScreenViewIter<'_> {
+    type Item = (usize, usize, bool);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (index, &on) = self.pixels.next()?;
+        Some((index % self.width, index / self.width, on))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.pixels.size_hint()
+    }
+}
+
+/// The `FrameEvents` struct summarizes what happened during a call to [`Emu::tick_frame`],
+/// so frontends can react to side effects without polling for them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct FrameEvents {
+    /// The screen was drawn to or cleared.
+    pub drew: bool,
+    /// The sound timer started running, see [`Emu::is_beeping`].
+    pub beep_started: bool,
+    /// The sound timer ran out, or was set to zero.
+    pub beep_stopped: bool,
+}
+
+/// The `DrawMode` enum represents how sprites are combined with the screen.
+/// Anything other than [`DrawMode::Xor`] is a debugging aid, programs rely on XOR drawing.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DrawMode {
+    /// Sprite pixels flip screen pixels, and flipping a pixel off is a collision.
+    #[default]
+    Xor,
+    /// Sprite pixels only ever turn screen pixels on, and there are never collisions.
+    /// This shows the shape of every sprite drawn, even ones that erase each other.
+    Or,
+}
+
+/// The `UnknownOpCodePolicy` enum represents what happens when the program runs into an opcode
+/// that is not a CHIP-8 instruction. Either way it is recorded, see [`Emu::unknown_opcodes_seen`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownOpCodePolicy {
+    /// Executing it fails with [`OpCodeError::UnknownOpCode`].
+    #[default]
+    Error,
+    /// It is skipped, so imperfect ROMs can keep running.
+    Nop,
+}
+
+/// The `ScreenTransform` enum represents how a frontend shows the screen, e.g. mirrored for an
+/// unusual terminal. It only moves pixels around on the way out, the screen itself is unchanged.
+/// With the `serde` feature, it can be saved in a frontend's settings.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum ScreenTransform {
+    /// The screen is shown as it is.
+    #[default]
+    None,
+    /// The screen is mirrored left to right.
+    FlipH,
+    /// The screen is mirrored top to bottom.
+    FlipV,
+    /// The screen is turned upside down.
+    Rotate180,
+}
+
+impl ScreenTransform {
+    #[must_use]
+    /// Returns where the pixel at (`x`, `y`) is shown. Every transform is its own inverse,
+    /// so this also gives the pixel to show at (`x`, `y`).
+    pub fn apply(self, x: usize, y: usize) -> (usize, usize) {
+        let (last_x, last_y) = (SCREEN_WIDTH - 1, SCREEN_HEIGHT - 1);
+        match self {
+            ScreenTransform::None => (x, y),
+            ScreenTransform::FlipH => (last_x - x, y),
+            ScreenTransform::FlipV => (x, last_y - y),
+            ScreenTransform::Rotate180 => (last_x - x, last_y - y),
+        }
+    }
+}
+
+impl FromStr for ScreenTransform {
+    type Err = String;
+
+    /// Parses `none`, `flip-h`, `flip-v` or `rotate180`, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Ok(ScreenTransform::None),
+            "flip-h" => Ok(ScreenTransform::FlipH),
+            "flip-v" => Ok(ScreenTransform::FlipV),
+            "rotate180" => Ok(ScreenTransform::Rotate180),
+            _ => Err(format!(
+                "unknown transform '{s}', expected none, flip-h, flip-v or rotate180"
+            )),
+        }
+    }
+}
+
+/// The `EmuWarning` enum represents problems that do not stop the emulator, but that a frontend
+/// may want to show the user. See [`Emu::take_warnings`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum EmuWarning {
+    /// The loaded ROM contains only zero bytes, so there is nothing to execute.
+    EmptyRom,
+    /// The program halted with subroutine calls that never returned still on the stack,
+    /// which often means a bug in the ROM. Holds how many calls are on the stack.
+    UnbalancedStack(u8),
+}
+
+impl Display for EmuWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EmuWarning::EmptyRom => write!(f, "ROM is empty, it contains only zero bytes"),
+            EmuWarning::UnbalancedStack(depth) => {
+                write!(f, "program halted with {depth} subroutine call(s) still on the stack")
+            }
+        }
+    }
+}
 
 impl Emu {
     /// Where the program counter starts.
-    const START_ADDRESS: u16 = 0x200;
+    pub(crate) const START_ADDRESS: u16 = PROGRAM_START;
+
+    /// How many subroutine calls the hardware stack can hold.
+    #[allow(clippy::cast_possible_truncation)]
+    const MAX_STACK_DEPTH: u8 = STACK_SIZE as u8;
 
     /// The start address of the ETI 660 CHIP-8 interpreter.
     const ETI_START_ADDRESS: u16 = 0x600;
 
+    /// One past the last addressable byte of RAM.
+    #[allow(clippy::cast_possible_truncation)]
+    const RAM_END: u16 = RAM_SIZE as u16;
+
+    /// Where the large character set is in RAM.
+    const LARGE_FONT: std::ops::Range<usize> =
+        LARGE_SPRITE_SET_ADDRESS..LARGE_SPRITE_SET_ADDRESS + LARGE_SPRITE_SET_SIZE;
+
+    /// How many times a second the delay and sound timers count down.
+    const TIMER_HZ: u32 = 60;
+
     #[must_use]
     #[allow(clippy::new_without_default)]
     /// Creates a new instance of the Emu struct.
@@ -68,31 +365,475 @@ impl Emu {
             stack: [0; STACK_SIZE],
             keys: [false; NUM_KEYS],
             screen: [false; SCREEN_WIDTH * SCREEN_HEIGHT],
+            dirty: false,
             keymapping: input::Input::default(),
+            warnings: Vec::new(),
+            rom: Vec::new(),
+            rom_start: Self::START_ADDRESS,
+            start_address: Self::START_ADDRESS,
+            quirks: Quirks::default(),
+            waiting_for_frame: false,
+            awaiting_key: false,
+            key_released: None,
+            draw_mode: DrawMode::default(),
+            unknown_opcode_policy: UnknownOpCodePolicy::default(),
+            last_timer_update: None,
+            frame: 0,
+            recording: None,
+            playback: VecDeque::new(),
+            max_stack_depth: Self::MAX_STACK_DEPTH,
+            font: SPRITE_SET,
+            total_cycles: 0,
+            rng: StdRng::from_entropy(),
+            unknown_opcodes: HashSet::new(),
+            watches: HashSet::new(),
+            watch_hits: Vec::new(),
+            #[cfg(feature = "sound")]
+            audio: super::sound::Audio::default(),
         };
 
-        // fill the first 80 bytes of memory with the character set
+        // fill the first 80 bytes of memory with the character set, then the large one
         // this works because we start at 0x200
-        emu.ram[0..SPRITE_SET_SIZE].copy_from_slice(&SPRITE_SET);
+        emu.ram[0..SPRITE_SET_SIZE].copy_from_slice(&emu.font);
+        emu.ram[Self::LARGE_FONT].copy_from_slice(&LARGE_SPRITE_SET);
+
+        emu
+    }
+
+    #[must_use]
+    /// Creates a new instance of the Emu struct with `ram` as its memory, e.g. a dumped state.
+    /// Each character set is only loaded if `ram` does not already have something there.
+    ///
+    /// # Arguments
+    /// * `ram`: the memory image to start with.
+    pub fn with_ram(ram: [u8; RAM_SIZE]) -> Self {
+        let mut emu = Self::new();
+        emu.ram = ram;
+        if emu.ram[0..SPRITE_SET_SIZE].iter().all(|&byte| byte == 0) {
+            emu.ram[0..SPRITE_SET_SIZE].copy_from_slice(&emu.font);
+        }
+        if emu.ram[Self::LARGE_FONT].iter().all(|&byte| byte == 0) {
+            emu.ram[Self::LARGE_FONT].copy_from_slice(&LARGE_SPRITE_SET);
+        }
+        emu
+    }
+
+    #[must_use]
+    /// Creates a new instance of the Emu struct with every byte of RAM zeroed, for tests that
+    /// check memory. Neither character set is loaded, so `FX29` and `FX30` point at zeros.
+    /// [`Emu::reset`] loads them again.
+    pub fn new_blank() -> Self {
+        let mut emu = Self::new();
+        emu.ram = [0; RAM_SIZE];
+        emu
+    }
 
+    #[must_use]
+    /// Starts building an emulator with options that can only be set up front, see [`EmuBuilder`].
+    pub fn builder() -> EmuBuilder {
+        EmuBuilder::default()
+    }
+
+    #[must_use]
+    /// Creates a new instance of the Emu struct with `input` as its keyboard layout,
+    /// e.g. [`Input::azerty`](input::Input::azerty).
+    ///
+    /// # Arguments
+    /// * `input`: the mapping from keyboard inputs to Chip-8 keys.
+    pub fn with_input(input: input::Input) -> Self {
+        let mut emu = Self::new();
+        emu.keymapping = input;
         emu
     }
 
-    //
-    // pub fn cycle() -> Result<EmuError> {
-    //     // 1. fetch_opcode
-    //     // 2. execute_opcode
-    // }
+    /// Runs a single fetch and execute cycle.
+    /// Nothing is executed while waiting for the next frame, see [`CycleOutcome::WaitingForFrame`].
+    ///
+    /// # Errors
+    /// Returns an [`EmuError::OpCodeError`] if the fetched opcode could not be executed.
+    pub fn cycle(&mut self) -> Result<CycleOutcome, EmuError> {
+        if self.waiting_for_frame {
+            return Ok(CycleOutcome::WaitingForFrame);
+        }
+        let pc = self.program_counter();
+        let opcode = self.fetch_opcode();
+        self.total_cycles += 1;
+        if opcode == OpCode::Unknown {
+            self.unknown_opcodes.insert(self.read_opcode(pc));
+        }
+        let watched = self.watched_values();
+        self.execute_opcode(&opcode)?;
+        self.record_watch_hits(&watched);
+        if self.awaiting_key {
+            return Ok(CycleOutcome::WaitingForKey);
+        }
+        if opcode == OpCode::Flow(1, pc) {
+            return Ok(CycleOutcome::Halted);
+        }
+        Ok(CycleOutcome::Executed)
+    }
+
+    /// Decodes and executes a single raw opcode, e.g. for a REPL or a test.
+    /// Unlike [`Emu::cycle`], nothing is fetched and the program counter is not advanced first,
+    /// so only what the opcode itself does changes the emulator: a skip moves the program
+    /// counter 2 bytes on from where it is, and a jump sets it.
+    ///
+    /// # Arguments
+    /// * `opcode`: the opcode to execute, e.g. `0x6005` to set V0 to 5.
+    ///
+    /// # Errors
+    /// Returns an [`EmuError::OpCodeError`] if the opcode could not be executed.
+    pub fn execute_raw(&mut self, opcode: u16) -> Result<(), EmuError> {
+        self.execute_opcode(&OpCode::from(opcode))?;
+        Ok(())
+    }
+
+    /// Runs up to `n` cycles, stopping early if the emulator is waiting for the next frame or
+    /// a key press, or has halted, see [`CycleOutcome`].
+    /// The instruction that halts or starts waiting for a key is counted, as it was executed.
+    ///
+    /// # Errors
+    /// Returns an [`EmuError::OpCodeError`] if an opcode could not be executed.
+    pub fn cycle_batch(&mut self, n: usize) -> Result<BatchResult, EmuError> {
+        for cycles in 0..n {
+            match self.cycle()? {
+                CycleOutcome::Executed => {}
+                CycleOutcome::WaitingForFrame => {
+                    return Ok(BatchResult {
+                        cycles,
+                        stopped: Some(CycleOutcome::WaitingForFrame),
+                    });
+                }
+                outcome => {
+                    return Ok(BatchResult {
+                        cycles: cycles + 1,
+                        stopped: Some(outcome),
+                    });
+                }
+            }
+        }
+        Ok(BatchResult {
+            cycles: n,
+            stopped: None,
+        })
+    }
+
+    /// Runs one frame: up to `instructions` cycles, stopping early if the emulator is waiting
+    /// for the next frame or a key press, or has halted, then ticks the timers.
+    /// This should be called 60 times a second.
+    /// Returns what happened during the frame, see [`FrameEvents`]. Drawing is reported even if
+    /// the screen was already marked as changed, which stays marked for [`Emu::take_dirty`].
+    ///
+    /// # Errors
+    /// Returns an [`EmuError::OpCodeError`] if an opcode could not be executed,
+    /// the timers are not ticked.
+    pub fn tick_frame(&mut self, instructions: usize) -> Result<FrameEvents, EmuError> {
+        let was_dirty = std::mem::take(&mut self.dirty);
+        let was_beeping = self.is_beeping();
+
+        while let Some(&(frame, event)) = self.playback.front() {
+            if frame > self.frame {
+                break;
+            }
+            self.playback.pop_front();
+            self.apply_input(event);
+        }
+
+        if let Err(err) = self.cycle_batch(instructions) {
+            self.dirty |= was_dirty;
+            return Err(err);
+        }
+        let drew = self.dirty;
+        self.dirty |= was_dirty;
+
+        self.tick_timers();
+        let beeping = self.is_beeping();
+        Ok(FrameEvents {
+            drew,
+            beep_started: beeping && !was_beeping,
+            beep_stopped: was_beeping && !beeping,
+        })
+    }
+
+    /// Runs frames of `instructions` cycles each until [`Emu::frame`] reaches `frame`,
+    /// e.g. to seek to a point in a replay.
+    ///
+    /// # Errors
+    /// Returns an [`EmuError::OpCodeError`] if an opcode could not be executed.
+    pub fn run_to_frame(&mut self, frame: u64, instructions: usize) -> Result<(), EmuError> {
+        while self.frame < frame {
+            self.tick_frame(instructions)?;
+        }
+        Ok(())
+    }
+
+    #[must_use]
+    /// Returns whether the program is stopped at an `FX0A` waiting for a key to be pressed and
+    /// released. Like the original hardware, the key is stored when it is released.
+    /// Frontends can wait for input rather than cycling while this is true.
+    pub fn awaiting_key(&self) -> bool {
+        self.awaiting_key
+    }
+
+    #[must_use]
+    /// Returns how many frames have passed since the emulator started or was reset.
+    /// Every call to [`Emu::tick_timers`] is a new frame.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    #[must_use]
+    /// Returns how many instructions have been run by [`Emu::cycle`] since the emulator started
+    /// or was reset, e.g. for profiling. Cycles spent waiting for the next frame do not count.
+    pub fn total_cycles(&self) -> u64 {
+        self.total_cycles
+    }
+
+    #[must_use]
+    /// Returns how many frames have passed since the emulator started or was reset,
+    /// the same as [`Emu::frame`]. Paired with [`Emu::total_cycles`] for stats.
+    pub fn total_frames(&self) -> u64 {
+        self.frame
+    }
+
+    /// Runs cycles until a `Display` opcode has been executed, so the screen can be
+    /// inspected one draw at a time. Each instruction runs through [`Emu::cycle`].
+    /// Also stops if the program halts or starts waiting for a key press or the next frame,
+    /// see [`CycleOutcome`].
+    /// Adds an [`EmuWarning::UnbalancedStack`] warning if the program ends inside a subroutine.
+    ///
+    /// # Errors
+    /// Returns an [`EmuError::OpCodeError`] if an opcode could not be executed.
+    pub fn run_until_display(&mut self) -> Result<(), EmuError> {
+        loop {
+            let opcode = OpCode::from(self.read_opcode(self.program_counter()));
+            match self.cycle()? {
+                CycleOutcome::Executed => {
+                    if matches!(opcode, OpCode::Display(_)) {
+                        return Ok(());
+                    }
+                }
+                CycleOutcome::Halted => {
+                    if self.stack_depth() > 0 {
+                        self.warnings.push(EmuWarning::UnbalancedStack(self.stack_depth()));
+                    }
+                    return Ok(());
+                }
+                CycleOutcome::WaitingForFrame | CycleOutcome::WaitingForKey => return Ok(()),
+            }
+        }
+    }
+
+    /// Loads a ROM into memory at the start address.
+    /// The emulator is [`Emu::reset`] first, so a game loaded mid-run starts clean rather than
+    /// inheriting the registers, stack and screen of the last one. Whatever was in memory from
+    /// the start address on is overwritten. Keys that are held down stay held.
+    /// Adds an [`EmuWarning::EmptyRom`] warning if the ROM contains only zero bytes.
+    /// Returns how many bytes were loaded.
+    ///
+    /// # Errors
+    /// Returns [`EmuError::RomLoadError`] if the ROM does not fit in memory.
+    /// The emulator is left untouched in that case.
+    pub fn load_rom(&mut self, rom: &[u8]) -> Result<usize, EmuError> {
+        let start = self.start_address as usize;
+        let end = start + rom.len();
+        if end > RAM_SIZE {
+            return Err(EmuError::RomLoadError);
+        }
+        self.reset_for_load();
+        self.copy_rom(rom, self.start_address);
+        Ok(rom.len())
+    }
+
+    /// Loads a ROM like [`Emu::load_rom`], and returns which interpreter it looks to be
+    /// written for, see [`detect_variant`], so frontends can tell the user.
+    /// The quirks are left alone, see [`Chip8Variant::quirks`] to match them to the ROM.
+    ///
+    /// # Errors
+    /// Returns [`EmuError::RomLoadError`] if the ROM does not fit in memory.
+    pub fn load_rom_detecting_variant(&mut self, rom: &[u8]) -> Result<Chip8Variant, EmuError> {
+        self.load_rom(rom)?;
+        Ok(detect_variant(rom))
+    }
+
+    /// Loads a ROM that is known to fit into memory at its start address,
+    /// and starts the program counter there.
+    /// Like [`Emu::load_rom`], the emulator is reset first.
+    /// Adds an [`EmuWarning::EmptyRom`] warning if the ROM contains only zero bytes.
+    ///
+    /// # Arguments
+    /// * `rom`: the ROM to load.
+    /// * `auto_quirks`: whether to set the quirks for the interpreter the ROM looks to be
+    ///   written for, see [`detect_variant`].
+    pub fn load_validated(&mut self, rom: &ValidRom, auto_quirks: bool) {
+        self.reset_for_load();
+        self.copy_rom(rom.bytes(), rom.start());
+        self.psuedo_registers.program_counter = rom.start();
+        if auto_quirks {
+            self.quirks = detect_variant(rom.bytes()).quirks();
+        }
+    }
+
+    /// Resets the emulator before loading a ROM, keeping the keys that are held down.
+    fn reset_for_load(&mut self) {
+        let keys = self.keys;
+        self.reset();
+        self.keys = keys;
+    }
+
+    /// Copies a ROM that fits in memory to `start`, remembering it for [`Emu::reset_preserving_rom`].
+    fn copy_rom(&mut self, rom: &[u8], start: u16) {
+        let start_index = start as usize;
+        self.ram[start_index..start_index + rom.len()].copy_from_slice(rom);
+        self.rom = rom.to_vec();
+        self.rom_start = start;
+
+        if rom.iter().all(|&byte| byte == 0) {
+            self.warnings.push(EmuWarning::EmptyRom);
+        }
+    }
+
+    /// Watches the byte of RAM at `address`, so [`Emu::cycle`] notes when an instruction changes
+    /// it, see [`Emu::take_watch_hits`]. Addresses outside RAM never change.
+    pub fn add_watch(&mut self, address: u16) {
+        self.watches.insert(address);
+    }
+
+    /// Stops watching the byte of RAM at `address`, see [`Emu::add_watch`].
+    pub fn remove_watch(&mut self, address: u16) {
+        self.watches.remove(&address);
+    }
+
+    #[must_use]
+    /// The audio output that plays the beep, only built with the `sound` feature.
+    #[cfg(feature = "sound")]
+    pub fn audio(&self) -> &super::sound::Audio {
+        &self.audio
+    }
+
+    /// Returns the watched addresses that changed value since the last call, clearing them.
+    /// An address is listed once for every instruction that changed it, in the order they ran.
+    pub fn take_watch_hits(&mut self) -> Vec<u16> {
+        std::mem::take(&mut self.watch_hits)
+    }
+
+    /// The watched addresses in RAM and the bytes they hold, in address order.
+    fn watched_values(&self) -> Vec<(u16, u8)> {
+        let mut values: Vec<(u16, u8)> = self
+            .watches
+            .iter()
+            .filter_map(|&address| Some((address, *self.ram.get(address as usize)?)))
+            .collect();
+        values.sort_unstable();
+        values
+    }
+
+    /// Notes every watched address that no longer holds the byte it did in `before`.
+    fn record_watch_hits(&mut self, before: &[(u16, u8)]) {
+        for &(address, value) in before {
+            if self.ram[address as usize] != value {
+                self.watch_hits.push(address);
+            }
+        }
+    }
+
+    #[must_use]
+    /// Returns the raw opcodes the program tried to run that are not CHIP-8 instructions,
+    /// e.g. from another variant, to put in a compatibility report. Cleared by [`Emu::reset`].
+    pub fn unknown_opcodes_seen(&self) -> &HashSet<u16> {
+        &self.unknown_opcodes
+    }
+
+    #[must_use]
+    /// Returns how many bytes of RAM are left after the loaded ROM, e.g. for tools that patch
+    /// ROMs. With no ROM loaded, this is everything from the start address on.
+    pub fn free_ram(&self) -> usize {
+        RAM_SIZE.saturating_sub(self.rom_start as usize + self.rom.len())
+    }
+
+    /// Returns the warnings noticed since the last call, clearing them.
+    pub fn take_warnings(&mut self) -> Vec<EmuWarning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    #[must_use]
+    /// Returns the interpreter behaviours the emulator follows.
+    pub fn quirks(&self) -> &Quirks {
+        &self.quirks
+    }
+
+    /// Sets the interpreter behaviours the emulator follows.
+    pub fn set_quirks(&mut self, quirks: Quirks) {
+        self.quirks = quirks;
+    }
+
+    /// Sets what happens when the program runs into an opcode that is not a CHIP-8 instruction,
+    /// see [`UnknownOpCodePolicy`].
+    pub fn set_unknown_opcode_policy(&mut self, policy: UnknownOpCodePolicy) {
+        self.unknown_opcode_policy = policy;
+    }
+
+    /// Seeds the random numbers `CXNN` uses, so the same program with the same input gets the
+    /// same numbers every run, e.g. for tests or replays. Unseeded, they differ each run.
+    pub fn set_rng_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    /// Sets how sprites are drawn. This is for debugging, see [`DrawMode`].
+    pub fn set_draw_mode(&mut self, draw_mode: DrawMode) {
+        self.draw_mode = draw_mode;
+    }
+
+    /// Sets which register `8XY6` and `8XYE` shift, see [`Quirks::shift`].
+    pub fn set_shift_quirk(&mut self, quirk: ShiftQuirk) {
+        self.quirks.shift = quirk;
+    }
+
+    /// Sets what `FX55` and `FX65` leave in `I`, see [`Quirks::memory`].
+    pub fn set_memory_quirk(&mut self, quirk: MemoryQuirk) {
+        self.quirks.memory = quirk;
+    }
+
+    /// Sets which register `BNNN` jumps relative to, see [`Quirks::jump`].
+    pub fn set_jump_quirk(&mut self, quirk: JumpQuirk) {
+        self.quirks.jump = quirk;
+    }
+
+    /// Sets whether `8XY1`, `8XY2` and `8XY3` clear VF, see [`Quirks::logic_vf_reset`].
+    pub fn set_logic_vf_reset(&mut self, reset: bool) {
+        self.quirks.logic_vf_reset = reset;
+    }
+
+    /// Limits how many subroutine calls can be nested, to catch runaway recursion before the
+    /// 16 level hardware stack fills up. Calling past the limit is an
+    /// [`OpCodeError::StackOverflow`]. The limit is capped at the size of the stack.
+    ///
+    /// # Arguments
+    /// * `depth`: the most addresses the stack may hold.
+    pub fn set_max_stack_depth(&mut self, depth: u8) {
+        self.max_stack_depth = depth.min(Self::MAX_STACK_DEPTH);
+    }
 
-    /// Sets the start address of the emulator.
+    /// Sets the start address of the emulator, e.g. 0x600 for ETI 660 programs, and moves the
+    /// program counter there. [`Emu::load_rom`] loads ROMs there and [`Emu::reset`] goes back
+    /// there, until it is set again.
     pub fn set_start_address(&mut self, address: u16) {
+        self.start_address = address;
         self.psuedo_registers.program_counter = address;
     }
 
-    /// Resets the emulator to its initial state.
+    /// Resets the emulator to its initial state, a cold reset like turning the power off and on.
     /// With character set loaded into memory as well.
+    /// Marks the screen as changed, see [`Emu::take_dirty`].
+    ///
+    /// There are three kinds of reset:
+    /// - [`Emu::reset`] clears everything, including the ROM.
+    /// - [`Emu::reset_preserving_rom`] clears everything, then loads the last ROM again.
+    /// - [`Emu::warm_reset`] only restarts the program, memory and registers are left as they are.
+    ///
+    /// The quirks, key mapping, start address and other settings are kept by all three.
     pub fn reset(&mut self) {
-        self.psuedo_registers.program_counter = Self::START_ADDRESS;
+        self.psuedo_registers.program_counter = self.start_address;
         self.psuedo_registers.stack_pointer = 0;
         self.special_registers = registers::SpecialRegisters::default();
         self.general_registers = registers::GeneralRegisters::default();
@@ -101,7 +842,163 @@ impl Emu {
         self.stack = [0; STACK_SIZE];
         self.keys = [false; NUM_KEYS];
         self.screen = [false; SCREEN_WIDTH * SCREEN_HEIGHT];
-        self.ram[0..SPRITE_SET_SIZE].copy_from_slice(&SPRITE_SET);
+        // the frontend needs to repaint the cleared screen
+        self.dirty = true;
+        self.warnings.clear();
+        self.waiting_for_frame = false;
+        self.awaiting_key = false;
+        self.key_released = None;
+        self.last_timer_update = None;
+        self.frame = 0;
+        self.total_cycles = 0;
+        self.unknown_opcodes.clear();
+        self.watch_hits.clear();
+        self.ram[0..SPRITE_SET_SIZE].copy_from_slice(&self.font);
+        self.ram[Self::LARGE_FONT].copy_from_slice(&LARGE_SPRITE_SET);
+        self.rom.clear();
+        self.rom_start = self.start_address;
+    }
+
+    /// Resets the emulator to its initial state, then loads the last ROM again.
+    /// The program restarts from the start address, as if it was just loaded.
+    /// See [`Emu::reset`] for the other kinds of reset.
+    pub fn reset_preserving_rom(&mut self) {
+        let rom = std::mem::take(&mut self.rom);
+        let start = self.rom_start;
+        self.reset();
+
+        self.copy_rom(&rom, start);
+        self.psuedo_registers.program_counter = start;
+    }
+
+    /// Restarts the program from the start address, like the reset button on some machines.
+    /// The stack, timers and any wait are cleared, but RAM, the registers and the screen are
+    /// left as they are, so a program that does not initialise them sees the old values.
+    /// See [`Emu::reset`] for the other kinds of reset.
+    pub fn warm_reset(&mut self) {
+        self.psuedo_registers.program_counter = self.rom_start;
+        self.psuedo_registers.stack_pointer = 0;
+        self.stack = [0; STACK_SIZE];
+        self.special_registers = registers::SpecialRegisters::default();
+        self.waiting_for_frame = false;
+        self.awaiting_key = false;
+        self.key_released = None;
+        self.last_timer_update = None;
+    }
+
+    #[must_use]
+    /// Returns the CPU state as a single line JSON object, for external debuggers:
+    /// ```text
+    /// {"pc":"0x200","i":"0x000","sp":0,"v":["0x00",...,"0x00"],"delay_timer":0,"sound_timer":0,"stack":[]}
+    /// ```
+    /// Addresses and registers are hex strings, the stack is oldest call first.
+    pub fn state_json(&self) -> String {
+        let hex_list = |items: Vec<String>| format!("[{}]", items.join(","));
+        let v = self
+            .registers()
+            .iter()
+            .map(|value| format!("\"{value:#04X}\""))
+            .collect();
+        let stack = self
+            .stack_contents()
+            .iter()
+            .map(|address| format!("\"{address:#05X}\""))
+            .collect();
+        format!(
+            "{{\"pc\":\"{:#05X}\",\"i\":\"{:#05X}\",\"sp\":{},\"v\":{},\"delay_timer\":{},\"sound_timer\":{},\"stack\":{}}}",
+            self.program_counter(),
+            self.i_register,
+            self.stack_pointer(),
+            hex_list(v),
+            self.special_registers.delay_timer,
+            self.special_registers.sound_timer,
+            hex_list(stack),
+        )
+    }
+
+    #[must_use]
+    /// Returns a hex dump of `len` bytes of RAM from `start`, 16 bytes per line,
+    /// each line being the address, the bytes in hex and the bytes as ASCII:
+    /// ```text
+    /// 0000: F0 90 90 90 F0 20 60 20 20 70 F0 10 F0 80 F0 F0  |..... `  p......|
+    /// ```
+    /// The range is clamped to the end of RAM.
+    ///
+    /// # Arguments
+    /// * `start`: the address of the first byte to dump.
+    /// * `len`: how many bytes to dump.
+    pub fn memory_dump_string(&self, start: u16, len: usize) -> String {
+        let start = (start as usize).min(RAM_SIZE);
+        let end = start.saturating_add(len).min(RAM_SIZE);
+
+        let mut dump = String::new();
+        for (line, bytes) in self.ram[start..end].chunks(16).enumerate() {
+            let _ = write!(dump, "{:04X}:", start + line * 16);
+            for byte in bytes {
+                let _ = write!(dump, " {byte:02X}");
+            }
+            // line the ascii column up on a short last line
+            dump.push_str(&"   ".repeat(16 - bytes.len()));
+
+            let ascii: String = bytes
+                .iter()
+                .map(|&byte| {
+                    if byte.is_ascii_graphic() || byte == b' ' {
+                        byte as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            let _ = writeln!(dump, "  |{ascii}|");
+        }
+        dump
+    }
+
+    #[must_use]
+    /// Compares the state of two emulators, e.g. to find where two runs diverged.
+    /// Returns a line for every register, timer, stack entry, RAM address and pixel that differs,
+    /// like `V3: 0x00 != 0x05`, with `self`'s value first. Differing quirks come first, on one line.
+    pub fn diff(&self, other: &Emu) -> Vec<String> {
+        let mut diffs = Vec::new();
+        if self.quirks != other.quirks {
+            diffs.push(format!("quirks: {:?} != {:?}", self.quirks, other.quirks));
+        }
+
+        let mut compare = |name: String, ours: u16, theirs: u16, width: usize| {
+            if ours != theirs {
+                diffs.push(format!("{name}: {ours:#0width$X} != {theirs:#0width$X}"));
+            }
+        };
+        compare("PC".into(), self.program_counter(), other.program_counter(), 6);
+        compare("I".into(), self.i_register, other.i_register, 6);
+        for register in 0..16 {
+            let (ours, theirs) = (self.get_register_val(register), other.get_register_val(register));
+            compare(format!("V{register:X}"), ours.into(), theirs.into(), 4);
+        }
+        compare("DT".into(), self.get_delay_timer().into(), other.get_delay_timer().into(), 4);
+        compare("ST".into(), self.get_sound_timer().into(), other.get_sound_timer().into(), 4);
+        compare("SP".into(), self.stack_pointer().into(), other.stack_pointer().into(), 4);
+        for (level, (&ours, &theirs)) in self.stack.iter().zip(&other.stack).enumerate() {
+            compare(format!("stack[{level}]"), ours, theirs, 6);
+        }
+        for (address, (&ours, &theirs)) in self.ram.iter().zip(&other.ram).enumerate() {
+            compare(format!("RAM[{address:#05X}]"), ours.into(), theirs.into(), 4);
+        }
+
+        let (width, _) = Self::screen_size();
+        let pixels = self.screen.iter().zip(&other.screen).enumerate();
+        for (index, (&ours, &theirs)) in pixels.filter(|(_, (ours, theirs))| ours != theirs) {
+            let on_off = |on: bool| if on { "on" } else { "off" };
+            diffs.push(format!(
+                "pixel ({}, {}): {} != {}",
+                index % width,
+                index / width,
+                on_off(ours),
+                on_off(theirs)
+            ));
+        }
+        diffs
     }
 
     #[must_use]
@@ -110,6 +1007,37 @@ impl Emu {
         (SCREEN_WIDTH, SCREEN_HEIGHT)
     }
 
+    /// Returns whether the screen changed since the last call, clearing the flag.
+    /// Frontends can use this to only redraw when needed.
+    pub fn take_dirty(&mut self) -> bool {
+        std::mem::take(&mut self.dirty)
+    }
+
+    /// Returns an iterator over the rows of the screen, top to bottom.
+    /// Each row is a slice of pixels, left to right, `true` meaning the pixel is on.
+    pub fn screen_rows(&self) -> impl Iterator<Item = &[bool]> {
+        let (screen_width, _) = Self::screen_size();
+        self.screen.chunks_exact(screen_width)
+    }
+
+    #[must_use]
+    /// Returns a view of the screen that iterates over every pixel as `(x, y, on)`,
+    /// e.g. `for (x, y, on) in emu.screen_view()`.
+    pub fn screen_view(&self) -> ScreenView<'_> {
+        let (width, height) = Self::screen_size();
+        ScreenView {
+            pixels: &self.screen,
+            width,
+            height,
+        }
+    }
+
+    #[must_use]
+    /// Returns the general purpose registers V0 to VF.
+    pub fn registers(&self) -> &[u8; 16] {
+        &self.general_registers.v
+    }
+
     pub(crate) fn get_register_val(&self, register: u8) -> u8 {
         self.general_registers.v[register as usize]
     }
@@ -118,7 +1046,9 @@ impl Emu {
         self.general_registers.v[register as usize] = val;
     }
 
-    pub(crate) fn program_counter(&self) -> u16 {
+    #[must_use]
+    /// Returns the address of the next instruction to execute.
+    pub fn program_counter(&self) -> u16 {
         self.psuedo_registers.program_counter
     }
 
@@ -126,6 +1056,32 @@ impl Emu {
         self.psuedo_registers.program_counter = address;
     }
 
+    /// Moves the program counter forward by one instruction (2 bytes).
+    /// Wraps around to the start of memory instead of overflowing past the end of RAM.
+    pub(crate) fn increment_program_counter(&mut self) {
+        let pc = self.psuedo_registers.program_counter.wrapping_add(OPCODE_SIZE);
+        self.psuedo_registers.program_counter = pc % Self::RAM_END;
+    }
+
+    /// Moves the program counter back by one instruction (2 bytes).
+    /// Wraps around to the end of memory instead of underflowing.
+    pub(crate) fn decrement_program_counter(&mut self) {
+        let pc = self.psuedo_registers.program_counter % Self::RAM_END;
+        self.psuedo_registers.program_counter = (pc + Self::RAM_END - OPCODE_SIZE) % Self::RAM_END;
+    }
+
+    #[must_use]
+    /// Returns how many subroutine calls are on the stack.
+    pub fn stack_depth(&self) -> u8 {
+        self.stack_pointer()
+    }
+
+    #[must_use]
+    /// Returns the return addresses of the subroutine calls on the stack, oldest first.
+    pub fn stack_contents(&self) -> &[u16] {
+        &self.stack[..self.stack_pointer() as usize]
+    }
+
     /// Returns the current stack pointer.
     pub(crate) fn stack_pointer(&self) -> u8 {
         self.psuedo_registers.stack_pointer
@@ -135,17 +1091,31 @@ impl Emu {
     ///
     /// # Arguments
     /// * `address`: the address to push onto the stack.
-    pub(crate) fn push_stack(&mut self, address: u16) {
+    ///
+    /// # Errors
+    /// Returns [`OpCodeError::StackOverflow`] if the stack is full, or already holds
+    /// [`Emu::set_max_stack_depth`] addresses.
+    pub(crate) fn push_stack(&mut self, address: u16) -> Result<(), OpCodeError> {
         let sp = self.stack_pointer();
+        if sp >= self.max_stack_depth {
+            return Err(OpCodeError::StackOverflow);
+        }
         self.stack[sp as usize] = address;
         self.psuedo_registers.stack_pointer += 1;
+        Ok(())
     }
 
     /// Pops the topmost address from the stack.
-    pub(crate) fn pop_stack(&mut self) -> u16 {
+    ///
+    /// # Errors
+    /// Returns [`OpCodeError::StackUnderflow`] if the stack is empty.
+    pub(crate) fn pop_stack(&mut self) -> Result<u16, OpCodeError> {
+        if self.stack_pointer() == 0 {
+            return Err(OpCodeError::StackUnderflow);
+        }
         self.psuedo_registers.stack_pointer -= 1;
         let sp = self.stack_pointer();
-        self.stack[sp as usize]
+        Ok(self.stack[sp as usize])
     }
 
     /// Gets the value of the delay timer register.
@@ -153,11 +1123,11 @@ impl Emu {
         self.special_registers.delay_timer
     }
 
-    /// Sets the value of the delay timer register.
+    /// Sets the value of the delay timer register, which counts down at 60Hz.
     ///
     /// # Arguments
     /// * `val`: the value to set the delay timer to.
-    pub(crate) fn set_delay_timer(&mut self, val: u8) {
+    pub fn set_delay_timer(&mut self, val: u8) {
         self.special_registers.delay_timer = val;
     }
 
@@ -166,20 +1136,41 @@ impl Emu {
         self.special_registers.sound_timer
     }
 
-    /// Sets the value of the sound timer register.
+    /// Sets the value of the sound timer register, which counts down at 60Hz.
+    /// The emulator beeps while it is above zero, see [`Emu::is_beeping`].
     ///
     /// # Arguments
-    /// * `val`: the value to set the delay timer to.
-    pub(crate) fn set_sound_timer(&mut self, val: u8) {
+    /// * `val`: the value to set the sound timer to.
+    pub fn set_sound_timer(&mut self, val: u8) {
         self.special_registers.sound_timer = val;
     }
 
-    /// Ticks the delay and sound timers if they are greater than 0.
-    /// Plays a sound if the sound timer is greater than 0.
-    pub(crate) fn tick_timers(&mut self) {
-        if self.special_registers.delay_timer > 0 {
-            self.special_registers.delay_timer -= 1;
-        }
+    #[must_use]
+    /// Returns how long until the delay timer reaches 0, counting down at 60Hz.
+    pub fn delay_remaining(&self) -> Duration {
+        Self::ticks_to_duration(self.special_registers.delay_timer)
+    }
+
+    #[must_use]
+    /// Returns how long until the sound timer reaches 0, counting down at 60Hz.
+    pub fn sound_remaining(&self) -> Duration {
+        Self::ticks_to_duration(self.special_registers.sound_timer)
+    }
+
+    fn ticks_to_duration(ticks: u8) -> Duration {
+        Duration::from_secs(u64::from(ticks)) / Self::TIMER_HZ
+    }
+
+    /// Ticks the delay and sound timers if they are greater than 0.
+    /// Plays a sound if the sound timer is greater than 0.
+    /// This should be called 60 times a second, it also marks the start of a new frame.
+    pub fn tick_timers(&mut self) {
+        self.waiting_for_frame = false;
+        self.frame += 1;
+
+        if self.special_registers.delay_timer > 0 {
+            self.special_registers.delay_timer -= 1;
+        }
 
         if self.special_registers.sound_timer > 0 {
             // #[cfg(feature = "sound")]
@@ -188,14 +1179,125 @@ impl Emu {
         }
     }
 
+    #[must_use]
+    /// Returns whether a sound should be playing, i.e. the sound timer is running.
+    pub fn is_beeping(&self) -> bool {
+        self.special_registers.sound_timer > 0
+    }
+
+    /// Ticks the timers once for every 60th of a second since the last call, so frontends
+    /// do not have to call [`Emu::tick_timers`] at exactly 60Hz.
+    /// The first call only starts the clock.
+    ///
+    /// # Arguments
+    /// * `now`: the current time, usually `Instant::now()`. Tests can pass any instant
+    ///   to step the timers without waiting.
+    pub fn update_timers(&mut self, now: Instant) {
+        let Some(last) = self.last_timer_update else {
+            self.last_timer_update = Some(now);
+            return;
+        };
+
+        let period = Duration::from_secs(1) / Self::TIMER_HZ;
+        let elapsed = now.saturating_duration_since(last);
+        let ticks = u32::try_from(elapsed.as_nanos() / period.as_nanos()).unwrap_or(u32::MAX);
+
+        // the timers are a single byte, so any more ticks would not change anything
+        for _ in 0..ticks.min(u32::from(u8::MAX)) {
+            self.tick_timers();
+        }
+        // keep the time left over, so it counts towards the next tick
+        self.last_timer_update = Some(last + period * ticks);
+    }
+
     /// Changes the state of a key to pressed.
     pub fn press_key(&mut self, key: usize) {
-        self.keys[key] = true;
+        self.apply_input(InputEvent::Press(key));
     }
 
     /// Changes the state of a key to unpressed.
     pub fn release_key(&mut self, key: usize) {
-        self.keys[key] = false;
+        self.apply_input(InputEvent::Release(key));
+    }
+
+    #[must_use]
+    /// Returns whether a Chip-8 key is currently pressed. Keys past the keypad are never pressed.
+    pub fn is_key_pressed(&self, key: usize) -> bool {
+        self.keys.get(key).copied().unwrap_or(false)
+    }
+
+    #[must_use]
+    /// Returns the Chip-8 keys that are currently pressed, in ascending order.
+    pub fn keys_pressed(&self) -> Vec<usize> {
+        (0..NUM_KEYS).filter(|&key| self.keys[key]).collect()
+    }
+
+    #[must_use]
+    /// Returns the Chip-8 keys that are currently pressed as a bitmask, bit `n` set meaning
+    /// key `n` is pressed.
+    pub fn keys_bitmask(&self) -> u16 {
+        self.keys_pressed()
+            .into_iter()
+            .fold(0, |mask, key| mask | (1 << key))
+    }
+
+    /// Presses and releases keys to match a bitmask like [`Emu::keys_bitmask`].
+    /// Only keys that change are pressed or released, so this is recorded and waited on by
+    /// `FX0A` just like [`Emu::press_key`] and [`Emu::release_key`].
+    pub fn set_keys_bitmask(&mut self, mask: u16) {
+        for key in 0..NUM_KEYS {
+            let pressed = mask & (1 << key) != 0;
+            if pressed && !self.keys[key] {
+                self.press_key(key);
+            } else if !pressed && self.keys[key] {
+                self.release_key(key);
+            }
+        }
+    }
+
+    #[must_use]
+    /// Returns the keyboard inputs mapped to the Chip-8 keys that are currently pressed,
+    /// in the order of their Chip-8 keys. Pressed keys with no input mapped are left out.
+    pub fn pressed_key_names(&self) -> Vec<String> {
+        self.keys_pressed()
+            .into_iter()
+            .filter_map(|key| self.keymapping.get_input_for_key(key))
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn apply_input(&mut self, event: InputEvent) {
+        match event {
+            InputEvent::Press(key) => self.keys[key] = true,
+            InputEvent::Release(key) => {
+                self.keys[key] = false;
+                if self.awaiting_key {
+                    self.key_released = Some(key);
+                }
+            }
+        }
+        if let Some(recording) = &mut self.recording {
+            recording.push((self.frame, event));
+        }
+    }
+
+    /// Starts recording key presses and releases, along with the frame they happened on.
+    /// Any previous recording is thrown away.
+    pub fn start_recording(&mut self) {
+        self.recording = Some(Vec::new());
+    }
+
+    /// Stops recording and returns the key presses and releases since [`Emu::start_recording`].
+    pub fn take_recording(&mut self) -> Vec<(u64, InputEvent)> {
+        self.recording.take().unwrap_or_default()
+    }
+
+    /// Replays recorded key presses and releases, each one at the start of the frame
+    /// it was recorded on, see [`Emu::tick_frame`].
+    /// Together with starting from the same state this makes a run reproducible.
+    pub fn play_recording(&mut self, mut events: Vec<(u64, InputEvent)>) {
+        events.sort_by_key(|&(frame, _)| frame);
+        self.playback = events.into();
     }
 
     #[must_use]
@@ -203,6 +1305,98 @@ impl Emu {
     pub fn get_key_mapping(&self, input: &str) -> Option<&usize> {
         self.keymapping.get_key_mapping(input)
     }
+
+    /// Maps a keyboard input to a Chip-8 key, unbinding any other input mapped to that key.
+    pub fn set_key_mapping(&mut self, input: &str, key: usize) {
+        self.keymapping.set_key_mapping(input, key);
+    }
+
+    /// Swaps the Chip-8 keys mapped to two keyboard inputs.
+    pub fn swap_key_mapping(&mut self, input_a: &str, input_b: &str) {
+        self.keymapping.swap_key_mapping(input_a, input_b);
+    }
+
+    /// Restores the default keyboard layout, see [`Input::reset_mapping`](input::Input::reset_mapping).
+    pub fn reset_key_mapping(&mut self) {
+        self.keymapping.reset_mapping();
+    }
+}
+
+/// The `EmuBuilder` struct sets up an [`Emu`] before it starts, see [`Emu::builder`].
+/// Anything left unset is the same as [`Emu::new`].
+#[derive(Debug, Default)]
+pub struct EmuBuilder {
+    input: Option<input::Input>,
+    quirks: Quirks,
+    initial_keys: [bool; NUM_KEYS],
+    font: Option<[u8; SPRITE_SET_SIZE]>,
+    rng_seed: Option<u64>,
+}
+
+impl EmuBuilder {
+    #[must_use]
+    /// Sets the mapping from keyboard inputs to Chip-8 keys.
+    pub fn input(mut self, input: input::Input) -> Self {
+        self.input = Some(input);
+        self
+    }
+
+    /// Sets the interpreter behaviours to follow.
+    ///
+    /// # Errors
+    /// Returns [`EmuError::InvalidQuirks`] if the quirks fail [`Quirks::validate`].
+    pub fn quirks(mut self, quirks: Quirks) -> Result<Self, EmuError> {
+        quirks.validate().map_err(EmuError::InvalidQuirks)?;
+        self.quirks = quirks;
+        Ok(self)
+    }
+
+    #[must_use]
+    /// Sets which Chip-8 keys are held down from the start, e.g. to test key opcodes
+    /// without pressing keys first.
+    pub fn initial_keys(mut self, keys: [bool; NUM_KEYS]) -> Self {
+        self.initial_keys = keys;
+        self
+    }
+
+    #[must_use]
+    /// Seeds the random numbers `CXNN` uses, see [`Emu::set_rng_seed`].
+    pub fn rng_seed(mut self, seed: u64) -> Self {
+        self.rng_seed = Some(seed);
+        self
+    }
+
+    /// Replaces the character set 0-F drawn by `FX29` with `font`, e.g. a themed or test font.
+    /// It is laid out like [`SPRITE_SET`], 5 bytes for each character.
+    ///
+    /// # Errors
+    /// Returns [`EmuError::InvalidFont`] if `font` is not [`SPRITE_SET_SIZE`] bytes long.
+    pub fn font(mut self, font: &[u8]) -> Result<Self, EmuError> {
+        let font = font
+            .try_into()
+            .map_err(|_| EmuError::InvalidFont(font.len()))?;
+        self.font = Some(font);
+        Ok(self)
+    }
+
+    #[must_use]
+    /// Creates the emulator.
+    pub fn build(self) -> Emu {
+        let mut emu = match self.input {
+            Some(input) => Emu::with_input(input),
+            None => Emu::new(),
+        };
+        emu.quirks = self.quirks;
+        emu.keys = self.initial_keys;
+        if let Some(font) = self.font {
+            emu.font = font;
+            emu.ram[0..SPRITE_SET_SIZE].copy_from_slice(&font);
+        }
+        if let Some(seed) = self.rng_seed {
+            emu.set_rng_seed(seed);
+        }
+        emu
+    }
 }
 
 #[cfg(test)]
@@ -221,6 +1415,18 @@ mod tests {
         assert_eq!(emu.stack, [0; STACK_SIZE]);
     }
 
+    #[test]
+    fn test_program_start() {
+        let emu = Emu::new();
+        assert_eq!(emu.program_counter(), PROGRAM_START);
+
+        let mut emu = Emu::new();
+        emu.load_rom(&[0x00, 0xE0]).unwrap();
+        assert_eq!(emu.program_counter(), PROGRAM_START);
+        emu.cycle().unwrap();
+        assert_eq!(emu.program_counter(), PROGRAM_START + OPCODE_SIZE);
+    }
+
     #[test]
     fn test_stack_pointer() {
         let emu = Emu::new();
@@ -232,20 +1438,836 @@ mod tests {
     fn test_push_stack() {
         let mut emu = Emu::new();
 
-        emu.push_stack(0x200);
+        emu.push_stack(0x200).unwrap();
 
         assert_eq!(emu.stack_pointer(), 1);
         assert_eq!(emu.stack[0], 0x200);
     }
 
+    #[test]
+    fn test_stack_contents() {
+        let mut emu = Emu::new();
+        assert!(emu.stack_contents().is_empty());
+
+        emu.push_stack(0x202).unwrap();
+        emu.push_stack(0x304).unwrap();
+
+        assert_eq!(emu.stack_contents(), &[0x202, 0x304]);
+    }
+
     #[test]
     fn test_pop_stack() {
         let mut emu = Emu::new();
 
-        emu.push_stack(0x200); // stack pointer is now 1
+        emu.push_stack(0x200).unwrap(); // stack pointer is now 1
 
-        assert_eq!(emu.pop_stack(), 0x200); // stack pointer is now 0
+        assert_eq!(emu.pop_stack(), Ok(0x200)); // stack pointer is now 0
         assert_eq!(emu.stack_pointer(), 0); // stack pointer is now 0
+        assert_eq!(emu.pop_stack(), Err(OpCodeError::StackUnderflow));
+    }
+
+    #[test]
+    fn test_push_full_stack() {
+        let mut emu = Emu::new();
+
+        for _ in 0..STACK_SIZE {
+            emu.push_stack(0x200).unwrap();
+        }
+        assert_eq!(emu.push_stack(0x200), Err(OpCodeError::StackOverflow));
+        assert_eq!(emu.stack_depth() as usize, STACK_SIZE);
+    }
+
+    #[test]
+    fn test_warm_reset() {
+        let mut emu = Emu::new();
+        // set V0, call a subroutine that beeps, then jump to itself
+        emu.load_rom(&[0x60, 0x05, 0x22, 0x06, 0x00, 0x00, 0xF0, 0x18, 0x12, 0x08])
+            .unwrap();
+        for _ in 0..3 {
+            emu.cycle().unwrap();
+        }
+        assert!(emu.is_beeping());
+
+        emu.warm_reset();
+        assert_eq!(emu.program_counter(), Emu::START_ADDRESS);
+        assert_eq!(emu.stack_depth(), 0);
+        assert!(!emu.is_beeping());
+        assert_eq!(emu.get_register_val(0), 5);
+        assert_eq!(emu.ram[0x200..0x202], [0x60, 0x05]);
+    }
+
+    #[test]
+    fn test_max_stack_depth() {
+        let mut emu = Emu::new();
+        emu.set_max_stack_depth(4);
+        // call itself forever
+        emu.load_rom(&[0x22, 0x00]).unwrap();
+
+        for _ in 0..4 {
+            emu.cycle().unwrap();
+        }
+        assert_eq!(
+            emu.cycle(),
+            Err(EmuError::OpCodeError(OpCodeError::StackOverflow))
+        );
+        assert_eq!(emu.stack_depth(), 4);
+
+        emu.set_max_stack_depth(u8::MAX);
+        assert_eq!(emu.max_stack_depth as usize, STACK_SIZE);
+    }
+
+    #[test]
+    fn test_new_blank() {
+        let emu = Emu::new_blank();
+        assert_eq!(emu.ram[0], 0);
+        assert!(emu.ram.iter().all(|&byte| byte == 0));
+        assert_eq!(emu.program_counter(), Emu::START_ADDRESS);
+    }
+
+    #[test]
+    fn test_with_ram() {
+        let mut ram = [0; RAM_SIZE];
+        ram[0x200] = 0x60;
+        ram[0x201] = 0x2A;
+
+        let emu = Emu::with_ram(ram);
+        assert_eq!(emu.program_counter(), Emu::START_ADDRESS);
+        assert_eq!(emu.peek_opcode(), Ok(OpCode::Constant((6, 0, 0x2A))));
+        // the image had no character set, so the default one is loaded
+        assert_eq!(&emu.ram[0..SPRITE_SET_SIZE], &SPRITE_SET);
+
+        // a character set in the image is kept
+        ram[0] = 0xAA;
+        let emu = Emu::with_ram(ram);
+        assert_eq!(emu.ram[0], 0xAA);
+        assert_eq!(emu.ram[1], 0);
+    }
+
+    #[test]
+    fn test_load_rom() {
+        let mut emu = Emu::new();
+
+        assert_eq!(emu.load_rom(&[0x60, 0x05]), Ok(2));
+        assert_eq!(emu.ram[0x200..0x202], [0x60, 0x05]);
+
+        emu.cycle().unwrap();
+        assert_eq!(emu.get_register_val(0), 0x05);
+        assert_eq!(emu.program_counter(), 0x202);
+
+        let too_large = [0; RAM_SIZE];
+        assert_eq!(emu.load_rom(&too_large), Err(EmuError::RomLoadError));
+    }
+
+    #[test]
+    fn test_load_rom_detecting_variant() {
+        let mut emu = Emu::new();
+        // switch to high resolution, then jump to itself
+        let variant = emu.load_rom_detecting_variant(&[0x00, 0xFF, 0x12, 0x02]);
+        assert_eq!(variant, Ok(Chip8Variant::SuperChip));
+        assert_eq!(emu.ram[0x200..0x202], [0x00, 0xFF]);
+
+        let variant = emu.load_rom_detecting_variant(&[0x00, 0xE0, 0x12, 0x02]);
+        assert_eq!(variant, Ok(Chip8Variant::Chip8));
+    }
+
+    #[test]
+    fn test_unknown_opcodes_seen() {
+        let mut emu = Emu::new();
+        // set V0 to 5, then an opcode no interpreter has
+        emu.load_rom(&[0x60, 0x05, 0xF0, 0xFF]).unwrap();
+        emu.cycle().unwrap();
+        assert!(emu.unknown_opcodes_seen().is_empty());
+
+        assert_eq!(
+            emu.cycle(),
+            Err(EmuError::OpCodeError(OpCodeError::UnknownOpCode))
+        );
+        assert_eq!(emu.unknown_opcodes_seen(), &HashSet::from([0xF0FF]));
+
+        emu.reset();
+        assert!(emu.unknown_opcodes_seen().is_empty());
+    }
+
+    #[test]
+    fn test_watch() {
+        let mut emu = Emu::new();
+        // set V0 to 123, I to 0x300, then store its digits at 0x300, 0x301 and 0x302
+        emu.load_rom(&[0x60, 0x7B, 0xA3, 0x00, 0xF0, 0x33]).unwrap();
+        emu.add_watch(0x302);
+        emu.add_watch(0x300);
+        emu.add_watch(0x400);
+
+        emu.cycle().unwrap();
+        emu.cycle().unwrap();
+        assert!(emu.take_watch_hits().is_empty());
+
+        emu.cycle().unwrap();
+        assert_eq!(emu.take_watch_hits(), vec![0x300, 0x302]);
+        assert!(emu.take_watch_hits().is_empty());
+
+        // the same digits again change nothing
+        emu.psuedo_registers.program_counter = 0x204;
+        emu.cycle().unwrap();
+        assert!(emu.take_watch_hits().is_empty());
+
+        // 45 changes all three digits, but only 0x302 is still watched
+        emu.remove_watch(0x300);
+        emu.set_register_val(0, 45);
+        emu.psuedo_registers.program_counter = 0x204;
+        emu.cycle().unwrap();
+        assert_eq!(emu.take_watch_hits(), vec![0x302]);
+    }
+
+    #[test]
+    fn test_unknown_opcode_policy() {
+        let mut emu = Emu::new();
+        emu.set_unknown_opcode_policy(UnknownOpCodePolicy::Nop);
+        // an opcode no interpreter has, then set V0 to 5
+        emu.load_rom(&[0xF0, 0xFF, 0x60, 0x05]).unwrap();
+
+        assert_eq!(emu.cycle(), Ok(CycleOutcome::Executed));
+        assert_eq!(emu.cycle(), Ok(CycleOutcome::Executed));
+        assert_eq!(emu.get_register_val(0), 5);
+        assert_eq!(emu.unknown_opcodes_seen(), &HashSet::from([0xF0FF]));
+    }
+
+    #[test]
+    fn test_free_ram() {
+        let mut emu = Emu::new();
+        assert_eq!(emu.free_ram(), RAM_SIZE - 0x200);
+
+        assert_eq!(emu.load_rom(&[0x00; 100]), Ok(100));
+        assert_eq!(emu.free_ram(), RAM_SIZE - 0x200 - 100);
+
+        let rom = ValidRom::from_bytes(&[0x12, 0x00], Emu::ETI_START_ADDRESS).unwrap();
+        emu.load_validated(&rom, false);
+        assert_eq!(emu.free_ram(), RAM_SIZE - Emu::ETI_START_ADDRESS as usize - 2);
+
+        // a start address past the end of RAM leaves no room at all
+        emu.set_start_address(0xFFFF);
+        emu.reset();
+        assert_eq!(emu.free_ram(), 0);
+    }
+
+    #[test]
+    fn test_load_rom_resets() {
+        let mut emu = Emu::new();
+        // set V0, call a subroutine, then clear the screen and jump to itself
+        emu.load_rom(&[0x60, 0x05, 0x22, 0x06, 0x00, 0x00, 0x00, 0xE0, 0x12, 0x08])
+            .unwrap();
+        emu.screen[0] = true;
+        emu.cycle().unwrap();
+        emu.cycle().unwrap();
+        assert_eq!(emu.stack_depth(), 1);
+
+        emu.load_rom(&[0x12, 0x00]).unwrap();
+        assert_eq!(emu.program_counter(), Emu::START_ADDRESS);
+        assert_eq!(emu.get_register_val(0), 0);
+        assert_eq!(emu.stack_depth(), 0);
+        assert!(!emu.screen[0]);
+        assert_eq!(emu.ram[0x202..0x204], [0x00, 0x00]);
+    }
+
+    #[test]
+    fn test_load_empty_rom() {
+        let mut emu = Emu::new();
+
+        emu.load_rom(&[0x00, 0x00]).unwrap();
+        assert_eq!(emu.take_warnings(), vec![EmuWarning::EmptyRom]);
+        assert!(emu.take_warnings().is_empty());
+
+        emu.load_rom(&[0x00, 0xE0]).unwrap();
+        assert!(emu.take_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_run_until_display() {
+        let mut emu = Emu::new();
+
+        emu.load_rom(&[
+            0x60, 0x05, // V0 = 5
+            0xA0, 0x00, // I = 0, the sprite for '0'
+            0xD0, 0x05, // draw it at (V0, V0)
+            0x12, 0x06, // jump to self
+        ])
+        .unwrap();
+        // loading resets the screen, which needs repainting
+        assert!(emu.take_dirty());
+        assert!(!emu.take_dirty());
+
+        emu.run_until_display().unwrap();
+        assert_eq!(emu.program_counter(), 0x206);
+        assert_eq!(emu.total_cycles(), 3);
+        assert!(emu.take_dirty());
+        assert!(!emu.take_dirty());
+
+        // nothing left to draw, we stop on the halt instead
+        emu.run_until_display().unwrap();
+        assert_eq!(emu.program_counter(), 0x206);
+        assert!(!emu.take_dirty());
+    }
+
+    #[test]
+    fn test_halt_with_unbalanced_stack() {
+        let mut emu = Emu::new();
+        // call a subroutine that jumps to itself rather than returning
+        emu.load_rom(&[0x22, 0x04, 0x00, 0xE0, 0x12, 0x04]).unwrap();
+
+        emu.run_until_display().unwrap();
+        assert_eq!(emu.program_counter(), 0x204);
+        assert_eq!(emu.stack_depth(), 1);
+        assert_eq!(emu.take_warnings(), vec![EmuWarning::UnbalancedStack(1)]);
+    }
+
+    #[test]
+    fn test_cycle_error() {
+        let mut emu = Emu::new();
+
+        emu.load_rom(&[0xFF, 0xFF]).unwrap();
+        assert_eq!(
+            emu.cycle(),
+            Err(EmuError::OpCodeError(OpCodeError::UnknownOpCode))
+        );
+    }
+
+    #[test]
+    fn test_screen_rows() {
+        let mut emu = Emu::new();
+        emu.screen[SCREEN_WIDTH + 3] = true;
+
+        let (width, height) = Emu::screen_size();
+        assert_eq!(emu.screen_rows().count(), height);
+        assert!(emu.screen_rows().all(|row| row.len() == width));
+        assert!(emu.screen_rows().nth(1).unwrap()[3]);
+    }
+
+    #[test]
+    fn test_screen_view() {
+        let mut emu = Emu::new();
+        // draw the 1 font sprite at (2, 1)
+        let rom = [0x61, 0x01, 0x62, 0x02, 0xF1, 0x29, 0xD2, 0x15];
+        emu.load_rom(&rom).unwrap();
+        for _ in 0..rom.len() / 2 {
+            emu.cycle().unwrap();
+        }
+
+        let view = emu.screen_view();
+        assert_eq!((view.width(), view.height()), Emu::screen_size());
+        assert_eq!(view.into_iter().count(), SCREEN_WIDTH * SCREEN_HEIGHT);
+
+        let lit: Vec<(usize, usize)> = emu
+            .screen_view()
+            .into_iter()
+            .filter(|&(_, _, on)| on)
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        // ..#. / .##. / ..#. / ..#. / .###
+        let expected = vec![
+            (4, 1),
+            (3, 2),
+            (4, 2),
+            (4, 3),
+            (4, 4),
+            (3, 5),
+            (4, 5),
+            (5, 5),
+        ];
+        assert_eq!(lit, expected);
+    }
+
+    #[test]
+    fn test_load_validated() {
+        // switch to high resolution, then jump to itself
+        let rom = ValidRom::try_from(&[0x00, 0xFF, 0x12, 0x02][..]).unwrap();
+
+        let mut emu = Emu::new();
+        emu.load_validated(&rom, false);
+        assert_eq!(emu.quirks(), &Quirks::default());
+        assert_eq!(&emu.ram[0x200..0x204], rom.bytes());
+
+        let mut emu = Emu::new();
+        emu.load_validated(&rom, true);
+        assert!(emu.quirks().clip);
+        assert_eq!(emu.quirks().jump, JumpQuirk::Vx);
+
+        // loaded somewhere other than the start address
+        let rom = ValidRom::from_bytes(&[0x60, 0x01], Emu::ETI_START_ADDRESS).unwrap();
+        emu.load_validated(&rom, true);
+        assert_eq!(emu.program_counter(), Emu::ETI_START_ADDRESS);
+        assert_eq!(emu.quirks(), &Quirks::default());
+
+        emu.cycle().unwrap();
+        emu.reset_preserving_rom();
+        assert_eq!(emu.program_counter(), Emu::ETI_START_ADDRESS);
+    }
+
+    #[test]
+    fn test_reset_sets_dirty() {
+        let mut emu = Emu::new();
+        emu.screen[0] = true;
+        emu.take_dirty();
+
+        emu.reset();
+        assert!(emu.take_dirty());
+        assert!(!emu.screen[0]);
+
+        emu.reset_preserving_rom();
+        assert!(emu.take_dirty());
+    }
+
+    #[test]
+    fn test_reset_keeps_start_address() {
+        let mut emu = Emu::new();
+        emu.set_start_address(Emu::ETI_START_ADDRESS);
+        emu.load_rom(&[0x60, 0x01, 0x16, 0x02]).unwrap();
+        assert_eq!(emu.program_counter(), 0x600);
+        assert_eq!(&emu.ram[0x600..0x604], &[0x60, 0x01, 0x16, 0x02]);
+        emu.cycle().unwrap();
+
+        emu.reset();
+        assert_eq!(emu.program_counter(), 0x600);
+    }
+
+    #[test]
+    fn test_reset_preserving_rom() {
+        let mut emu = Emu::new();
+        emu.load_rom(&[0x60, 0x01, 0x12, 0x02]).unwrap();
+        emu.cycle().unwrap();
+        emu.cycle().unwrap();
+
+        emu.reset_preserving_rom();
+
+        assert_eq!(emu.program_counter(), Emu::START_ADDRESS);
+        assert_eq!(emu.get_register_val(0), 0);
+        assert_eq!(&emu.ram[0x200..0x204], &[0x60, 0x01, 0x12, 0x02]);
+    }
+
+    #[test]
+    fn test_clone() {
+        let mut emu = Emu::new();
+        // count up in V0 forever, drawing each time
+        emu.load_rom(&[0x70, 0x01, 0xD0, 0x05, 0x12, 0x00]).unwrap();
+        emu.press_key(4);
+        emu.tick_frame(25).unwrap();
+
+        let mut clone = emu.clone();
+        assert!(clone.diff(&emu).is_empty());
+        assert_eq!(clone.frame(), emu.frame());
+
+        // the clone runs on its own
+        clone.tick_frame(10).unwrap();
+        assert!(!clone.diff(&emu).is_empty());
+    }
+
+    #[test]
+    fn test_diff() {
+        let emu = Emu::new();
+        let mut other = Emu::new();
+        assert!(emu.diff(&other).is_empty());
+
+        other.set_register_val(3, 5);
+        assert_eq!(emu.diff(&other), vec!["V3: 0x00 != 0x05"]);
+
+        let mut shifted = Emu::new();
+        shifted.set_shift_quirk(ShiftQuirk::CopyY);
+        assert_eq!(emu.diff(&shifted).len(), 1);
+        assert!(emu.diff(&shifted)[0].starts_with("quirks: "));
+
+        other.screen[SCREEN_WIDTH + 2] = true;
+        other.ram[0x300] = 0xAB;
+        assert_eq!(
+            emu.diff(&other),
+            vec![
+                "V3: 0x00 != 0x05",
+                "RAM[0x300]: 0x00 != 0xAB",
+                "pixel (2, 1): off != on"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_state_json() {
+        let mut emu = Emu::new();
+        let json = emu.state_json();
+        assert!(json.contains("\"pc\":\"0x200\""));
+        assert!(json.contains("\"i\":\"0x000\""));
+        assert!(json.contains("\"stack\":[]"));
+
+        // set V1 to 0xAB, then call 0x300
+        emu.load_rom(&[0x61, 0xAB, 0x23, 0x00]).unwrap();
+        emu.cycle().unwrap();
+        emu.cycle().unwrap();
+        let json = emu.state_json();
+        assert!(json.starts_with("{\"pc\":\"0x300\","));
+        assert!(json.contains("\"sp\":1,\"v\":[\"0x00\",\"0xAB\",\"0x00\""));
+        assert!(json.ends_with("\"sound_timer\":0,\"stack\":[\"0x204\"]}"));
+    }
+
+    #[test]
+    fn test_memory_dump_string() {
+        let emu = Emu::new();
+
+        let dump = emu.memory_dump_string(0, 20);
+        let mut lines = dump.lines();
+        assert!(lines
+            .next()
+            .unwrap()
+            .starts_with("0000: F0 90 90 90 F0 20 60 20 20 70 F0 10 F0 80 F0 F0  |"));
+        assert_eq!(
+            lines.next().unwrap(),
+            format!("0010: 10 F0 10 F0{}  |....|", "   ".repeat(12))
+        );
+        assert_eq!(lines.next(), None);
+
+        // clamped to the end of RAM
+        assert_eq!(emu.memory_dump_string(Emu::RAM_END - 2, 16).lines().count(), 1);
+        assert_eq!(emu.memory_dump_string(Emu::RAM_END, 16), "");
+    }
+
+    #[test]
+    fn test_frame_counter() {
+        let mut emu = Emu::new();
+        // jump to itself
+        emu.load_rom(&[0x12, 0x00]).unwrap();
+
+        for _ in 0..10 {
+            emu.tick_frame(10).unwrap();
+        }
+        assert_eq!(emu.frame(), 10);
+
+        emu.run_to_frame(25, 10).unwrap();
+        assert_eq!(emu.frame(), 25);
+
+        emu.reset();
+        assert_eq!(emu.frame(), 0);
+    }
+
+    #[test]
+    fn test_recording() {
+        // jump to itself
+        let rom = [0x12, 0x00];
+
+        let mut emu = Emu::new();
+        emu.load_rom(&rom).unwrap();
+        emu.start_recording();
+        for _ in 0..3 {
+            emu.tick_frame(10).unwrap();
+        }
+        emu.press_key(5);
+        emu.tick_frame(10).unwrap();
+        emu.release_key(5);
+        let recording = emu.take_recording();
+        assert_eq!(
+            recording,
+            vec![(3, InputEvent::Press(5)), (4, InputEvent::Release(5))]
+        );
+
+        let mut replay = Emu::new();
+        replay.load_rom(&rom).unwrap();
+        replay.play_recording(recording);
+        for _ in 0..3 {
+            replay.tick_frame(10).unwrap();
+            assert!(!replay.keys[5]);
+        }
+        replay.tick_frame(10).unwrap();
+        assert!(replay.keys[5]);
+        replay.tick_frame(10).unwrap();
+        assert!(!replay.keys[5]);
+    }
+
+    #[test]
+    fn test_awaiting_key() {
+        let mut emu = Emu::new();
+        // wait for a key in V0, then jump to itself
+        emu.load_rom(&[0xF0, 0x0A, 0x12, 0x02]).unwrap();
+        assert!(!emu.awaiting_key());
+
+        assert_eq!(emu.cycle(), Ok(CycleOutcome::WaitingForKey));
+        assert!(emu.awaiting_key());
+        assert_eq!(emu.cycle(), Ok(CycleOutcome::WaitingForKey));
+        assert_eq!(emu.program_counter(), 0x200);
+
+        // the key is only stored once it is released
+        emu.press_key(7);
+        assert_eq!(emu.cycle(), Ok(CycleOutcome::WaitingForKey));
+        emu.release_key(7);
+        assert_eq!(emu.cycle(), Ok(CycleOutcome::Executed));
+        assert!(!emu.awaiting_key());
+        assert_eq!(emu.get_register_val(0), 7);
+    }
+
+    #[test]
+    fn test_awaiting_key_stores_released_key() {
+        let mut emu = Emu::new();
+        // wait for a key in V0, then jump to itself
+        emu.load_rom(&[0xF0, 0x0A, 0x12, 0x02]).unwrap();
+        assert_eq!(emu.cycle(), Ok(CycleOutcome::WaitingForKey));
+
+        emu.press_key(5);
+        emu.press_key(2);
+        emu.release_key(5);
+        assert_eq!(emu.cycle(), Ok(CycleOutcome::Executed));
+        assert_eq!(emu.get_register_val(0), 5);
+    }
+
+    #[test]
+    fn test_cycle_batch() {
+        let mut emu = Emu::new();
+        // set V0 to 1 and V1 to 2, then jump to itself
+        emu.load_rom(&[0x60, 0x01, 0x61, 0x02, 0x12, 0x04]).unwrap();
+
+        assert_eq!(
+            emu.cycle_batch(1),
+            Ok(BatchResult {
+                cycles: 1,
+                stopped: None
+            })
+        );
+        // halts on the third instruction, well before the end of the batch
+        assert_eq!(
+            emu.cycle_batch(10),
+            Ok(BatchResult {
+                cycles: 2,
+                stopped: Some(CycleOutcome::Halted)
+            })
+        );
+        assert_eq!(emu.total_cycles(), 3);
+    }
+
+    #[test]
+    fn test_execute_raw() {
+        let mut emu = Emu::new();
+        emu.execute_raw(0x6005).unwrap();
+        assert_eq!(emu.get_register_val(0), 5);
+        assert_eq!(emu.program_counter(), Emu::START_ADDRESS);
+        assert_eq!(emu.total_cycles(), 0);
+
+        assert!(emu.execute_raw(0xFFFF).is_err());
+    }
+
+    #[test]
+    fn test_set_timers() {
+        let mut emu = Emu::new();
+        emu.set_sound_timer(2);
+        emu.set_delay_timer(1);
+        assert!(emu.is_beeping());
+
+        emu.tick_timers();
+        assert!(emu.is_beeping());
+        assert_eq!(emu.delay_remaining(), Duration::ZERO);
+
+        emu.tick_timers();
+        assert!(!emu.is_beeping());
+    }
+
+    #[test]
+    fn test_screen_transform() {
+        let (width, height) = Emu::screen_size();
+        assert_eq!(ScreenTransform::None.apply(0, 0), (0, 0));
+        // the top left pixel is shown top right
+        assert_eq!(ScreenTransform::FlipH.apply(0, 0), (width - 1, 0));
+        assert_eq!(ScreenTransform::FlipV.apply(0, 0), (0, height - 1));
+        assert_eq!(ScreenTransform::Rotate180.apply(1, 2), (width - 2, height - 3));
+        for transform in [ScreenTransform::FlipH, ScreenTransform::Rotate180] {
+            let (x, y) = transform.apply(5, 7);
+            assert_eq!(transform.apply(x, y), (5, 7));
+        }
+
+        assert_eq!("Flip-H".parse(), Ok(ScreenTransform::FlipH));
+        assert!("sideways".parse::<ScreenTransform>().is_err());
+    }
+
+    #[test]
+    fn test_with_input() {
+        let emu = Emu::with_input(input::Input::azerty());
+        assert_eq!(emu.get_key_mapping("a"), Some(&4));
+        assert_eq!(emu.get_key_mapping("&"), Some(&1));
+    }
+
+    #[test]
+    fn test_frame_events() {
+        let mut emu = Emu::new();
+        // beep for 3 frames, draw, then jump to itself
+        emu.load_rom(&[0x60, 0x03, 0xF0, 0x18, 0xD0, 0x01, 0x12, 0x06]).unwrap();
+
+        let events = emu.tick_frame(10).unwrap();
+        assert!(events.drew);
+        assert!(events.beep_started);
+        assert!(!events.beep_stopped);
+        // the draw is still there for the frontend to pick up
+        assert!(emu.take_dirty());
+
+        assert_eq!(emu.tick_frame(10), Ok(FrameEvents::default()));
+        let events = emu.tick_frame(10).unwrap();
+        assert!(events.beep_stopped && !events.drew);
+    }
+
+    #[test]
+    fn test_total_cycles_and_frames() {
+        let mut emu = Emu::new();
+        emu.set_quirks(Quirks {
+            display_wait: true,
+            ..Quirks::default()
+        });
+        // count up in V0, draw, then loop back
+        emu.load_rom(&[0x70, 0x01, 0xD0, 0x01, 0x12, 0x00]).unwrap();
+
+        emu.cycle().unwrap();
+        assert_eq!(emu.total_cycles(), 1);
+        // the draw ends the frame, cycles waiting for the next one do not count
+        for _ in 0..3 {
+            emu.tick_frame(10).unwrap();
+        }
+        assert_eq!(emu.total_cycles(), 1 + 1 + 3 + 3);
+        assert_eq!(emu.total_frames(), 3);
+
+        emu.take_dirty();
+        assert_eq!(emu.total_cycles(), 8);
+
+        emu.reset();
+        assert_eq!(emu.total_cycles(), 0);
+        assert_eq!(emu.total_frames(), 0);
+    }
+
+    #[test]
+    fn test_cycle_halted() {
+        let mut emu = Emu::new();
+        // count up in V0, then jump to itself
+        emu.load_rom(&[0x70, 0x01, 0x12, 0x02]).unwrap();
+
+        assert_eq!(emu.cycle(), Ok(CycleOutcome::Executed));
+        assert_eq!(emu.cycle(), Ok(CycleOutcome::Halted));
+        assert_eq!(emu.cycle(), Ok(CycleOutcome::Halted));
+
+        // the frame ends on the halt rather than spinning through every instruction
+        emu.tick_frame(1000).unwrap();
+        assert_eq!(emu.get_register_val(0), 1);
+        assert_eq!(emu.frame(), 1);
+    }
+
+    #[test]
+    fn test_builder_quirks() {
+        let clip = Quirks {
+            clip: true,
+            ..Quirks::default()
+        };
+        assert_eq!(clip.validate(), Ok(()));
+        let emu = Emu::builder().quirks(clip).unwrap().build();
+        assert_eq!(emu.quirks(), &clip);
+
+        let contradictory = Quirks {
+            wrap_y: true,
+            ..clip
+        };
+        assert_eq!(contradictory.validate(), Err(QuirksError::ClipAndWrapY));
+        assert_eq!(
+            Emu::builder().quirks(contradictory).err(),
+            Some(EmuError::InvalidQuirks(QuirksError::ClipAndWrapY))
+        );
+    }
+
+    #[test]
+    fn test_builder_initial_keys() {
+        let mut keys = [false; NUM_KEYS];
+        keys[5] = true;
+        let mut emu = Emu::builder().initial_keys(keys).build();
+        assert!(emu.is_key_pressed(5));
+        assert!(!emu.is_key_pressed(4));
+
+        // skip the next instruction if the key in V0 is held, which it already is
+        emu.load_rom(&[0x60, 0x05, 0xE0, 0x9E]).unwrap();
+        emu.cycle().unwrap();
+        emu.cycle().unwrap();
+        assert_eq!(emu.program_counter(), 0x206);
+    }
+
+    #[test]
+    fn test_keys_bitmask() {
+        let mut emu = Emu::new();
+        assert_eq!(emu.keys_bitmask(), 0);
+
+        emu.set_keys_bitmask(0b1001);
+        assert!(emu.is_key_pressed(0));
+        assert!(emu.is_key_pressed(3));
+        assert_eq!(emu.keys_pressed(), vec![0, 3]);
+        assert_eq!(emu.keys_bitmask(), 0b1001);
+
+        emu.start_recording();
+        emu.set_keys_bitmask(0x8001);
+        assert_eq!(emu.keys_pressed(), vec![0, 15]);
+        assert_eq!(
+            emu.take_recording(),
+            vec![(0, InputEvent::Release(3)), (0, InputEvent::Press(15))]
+        );
+    }
+
+    #[test]
+    fn test_pressed_key_names() {
+        let mut emu = Emu::new();
+        assert!(emu.pressed_key_names().is_empty());
+
+        emu.press_key(0);
+        emu.press_key(5);
+        assert_eq!(emu.keys_pressed(), vec![0, 5]);
+        assert_eq!(emu.pressed_key_names(), vec!["x", "w"]);
+
+        emu.release_key(5);
+        assert_eq!(emu.pressed_key_names(), vec!["x"]);
+    }
+
+    #[test]
+    fn test_display_wait() {
+        let mut emu = Emu::new();
+        emu.set_quirks(Quirks {
+            display_wait: true,
+            ..Quirks::default()
+        });
+        // draw, then loop back to draw again
+        emu.load_rom(&[0xD0, 0x01, 0x12, 0x00]).unwrap();
+
+        assert_eq!(emu.cycle(), Ok(CycleOutcome::Executed));
+        assert_eq!(emu.cycle(), Ok(CycleOutcome::WaitingForFrame));
+        assert_eq!(emu.program_counter(), 0x202);
+
+        emu.tick_timers();
+        assert_eq!(emu.cycle(), Ok(CycleOutcome::Executed));
+        assert_eq!(emu.program_counter(), 0x200);
+    }
+
+    #[test]
+    fn test_timer_remaining() {
+        let mut emu = Emu::new();
+        emu.set_delay_timer(60);
+        emu.set_sound_timer(30);
+
+        assert_eq!(emu.delay_remaining(), Duration::from_secs(1));
+        assert_eq!(emu.sound_remaining(), Duration::from_millis(500));
+        assert!(emu.is_beeping());
+
+        emu.set_sound_timer(0);
+        assert!(!emu.is_beeping());
+    }
+
+    #[test]
+    fn test_update_timers() {
+        let mut emu = Emu::new();
+        let start = Instant::now();
+        emu.set_delay_timer(10);
+        emu.set_sound_timer(2);
+
+        emu.update_timers(start);
+        assert_eq!(emu.get_delay_timer(), 10);
+
+        emu.update_timers(start + Duration::from_millis(40));
+        assert_eq!(emu.get_delay_timer(), 8);
+        assert_eq!(emu.get_sound_timer(), 0);
+
+        // the ~7ms left over from the last update counts towards this one
+        emu.update_timers(start + Duration::from_millis(55));
+        assert_eq!(emu.get_delay_timer(), 7);
+
+        emu.update_timers(start + Duration::from_secs(10));
+        assert_eq!(emu.get_delay_timer(), 0);
     }
 
     #[test]
@@ -1,7 +1,17 @@
 /// This module contains the input struct which maps keyboard inputs to the CHIP-8 keys.
 use std::collections::HashMap;
 
-#[derive(Debug)]
+/// The `InputEvent` enum represents a change to the state of a CHIP-8 key,
+/// as recorded by [`Emu::start_recording`](super::emulator::Emu::start_recording).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputEvent {
+    /// The CHIP-8 key was pressed.
+    Press(usize),
+    /// The CHIP-8 key was released.
+    Release(usize),
+}
+
+#[derive(Debug, Clone)]
 /// The Input struct is used to map keyboard inputs to CHIP-8 keys.
 pub struct Input {
     keymapping: HashMap<String, usize>,
@@ -9,31 +19,84 @@ pub struct Input {
 
 impl Default for Input {
     fn default() -> Self {
-        let keys = [
-            ("x".to_string(), 0),
-            ("1".to_string(), 1),
-            ("2".to_string(), 2),
-            ("3".to_string(), 3),
-            ("q".to_string(), 4),
-            ("w".to_string(), 5),
-            ("e".to_string(), 6),
-            ("a".to_string(), 7),
-            ("s".to_string(), 8),
-            ("d".to_string(), 9),
-            ("z".to_string(), 10),
-            ("c".to_string(), 11),
-            ("4".to_string(), 12),
-            ("r".to_string(), 13),
-            ("f".to_string(), 14),
-            ("v".to_string(), 15),
-        ];
-        Self {
-            keymapping: keys.iter().cloned().collect(),
-        }
+        Self::qwerty()
     }
 }
 
 impl Input {
+    #[must_use]
+    /// Maps the left of a QWERTY keyboard onto the CHIP-8 keypad:
+    /// ```text
+    /// 1 2 3 4      1 2 3 C
+    /// q w e r  ->  4 5 6 D
+    /// a s d f      7 8 9 E
+    /// z x c v      A 0 B F
+    /// ```
+    pub fn qwerty() -> Self {
+        Self::from_pairs(&[
+            ("x", 0),
+            ("1", 1),
+            ("2", 2),
+            ("3", 3),
+            ("q", 4),
+            ("w", 5),
+            ("e", 6),
+            ("a", 7),
+            ("s", 8),
+            ("d", 9),
+            ("z", 10),
+            ("c", 11),
+            ("4", 12),
+            ("r", 13),
+            ("f", 14),
+            ("v", 15),
+        ])
+    }
+
+    #[must_use]
+    /// Maps the same physical keys as [`Input::qwerty`] on an AZERTY keyboard.
+    /// The top row uses the characters typed without shift:
+    /// ```text
+    /// & é " '      1 2 3 C
+    /// a z e r  ->  4 5 6 D
+    /// q s d f      7 8 9 E
+    /// w x c v      A 0 B F
+    /// ```
+    pub fn azerty() -> Self {
+        Self::from_pairs(&[
+            ("x", 0),
+            ("&", 1),
+            ("é", 2),
+            ("\"", 3),
+            ("a", 4),
+            ("z", 5),
+            ("e", 6),
+            ("q", 7),
+            ("s", 8),
+            ("d", 9),
+            ("w", 10),
+            ("c", 11),
+            ("'", 12),
+            ("r", 13),
+            ("f", 14),
+            ("v", 15),
+        ])
+    }
+
+    #[must_use]
+    /// Creates a mapping from pairs of keyboard input and CHIP-8 key.
+    ///
+    /// # Arguments
+    /// * `pairs`: the keyboard inputs and the CHIP-8 key each one maps to.
+    pub fn from_pairs(pairs: &[(&str, usize)]) -> Self {
+        Self {
+            keymapping: pairs
+                .iter()
+                .map(|&(input, key)| (input.to_string(), key))
+                .collect(),
+        }
+    }
+
     /// Sets a new mapping for a keyboard input to a CHIP-8 key.
     /// 
     /// # Arguments
@@ -43,12 +106,44 @@ impl Input {
         self.keymapping.retain(|_, &mut v| v != key);
         self.keymapping.insert(input.to_string(), key);
     }
-    
+
+    /// Swaps the CHIP-8 keys mapped to two keyboard inputs.
+    /// If only one of the inputs is mapped, its key is moved to the other input.
+    ///
+    /// # Arguments
+    /// * `input_a`: the first keyboard input.
+    /// * `input_b`: the second keyboard input.
+    pub fn swap_key_mapping(&mut self, input_a: &str, input_b: &str) {
+        let key_a = self.keymapping.remove(input_a);
+        let key_b = self.keymapping.remove(input_b);
+
+        if let Some(key) = key_b {
+            self.keymapping.insert(input_a.to_string(), key);
+        }
+        if let Some(key) = key_a {
+            self.keymapping.insert(input_b.to_string(), key);
+        }
+    }
+
+    /// Restores the default mapping, [`Input::qwerty`], undoing any remapping.
+    pub fn reset_mapping(&mut self) {
+        *self = Self::default();
+    }
+
     #[must_use]
     /// Gets the CHIP-8 key mapped to a keyboard input.
     pub(crate) fn get_key_mapping(&self, input: &str) -> Option<&usize> {
         self.keymapping.get(input)
     }
+
+    #[must_use]
+    /// Gets the keyboard input mapped to a CHIP-8 key.
+    pub(crate) fn get_input_for_key(&self, key: usize) -> Option<&str> {
+        self.keymapping
+            .iter()
+            .find(|&(_, &k)| k == key)
+            .map(|(input, _)| input.as_str())
+    }
 }
 
 #[cfg(test)]
@@ -61,6 +156,19 @@ mod tests {
         assert_eq!(input.keymapping.len(), 16);
     }
 
+    #[test]
+    fn test_azerty() {
+        let input = Input::azerty();
+        assert_eq!(input.keymapping.len(), 16);
+        assert_eq!(input.get_key_mapping("&"), Some(&0x1));
+        assert_eq!(input.get_key_mapping("'"), Some(&0xC));
+        assert_eq!(input.get_key_mapping("a"), Some(&0x4));
+        assert_eq!(input.get_key_mapping("z"), Some(&0x5));
+        assert_eq!(input.get_key_mapping("q"), Some(&0x7));
+        assert_eq!(input.get_key_mapping("w"), Some(&0xA));
+        assert_eq!(input.get_key_mapping("x"), Some(&0x0));
+    }
+
     #[test]
     fn test_set_key_mapping() {
         let mut input = Input::default();
@@ -70,10 +178,35 @@ mod tests {
         assert_eq!(input.keymapping.len(), 16);
     }
 
+    #[test]
+    fn test_swap_key_mapping() {
+        let mut input = Input::default();
+        input.swap_key_mapping("x", "1");
+        assert_eq!(input.get_key_mapping("x"), Some(&0x1));
+        assert_eq!(input.get_key_mapping("1"), Some(&0x0));
+        assert_eq!(input.keymapping.len(), 16);
+    }
+
+    #[test]
+    fn test_reset_mapping() {
+        let mut input = Input::default();
+        input.set_key_mapping("t", 0x0);
+        input.swap_key_mapping("1", "2");
+        input.reset_mapping();
+        assert_eq!(input.keymapping, Input::default().keymapping);
+    }
+
     #[test]
     fn test_get_key_mapping() {
         let input = Input::default();
         assert_eq!(input.get_key_mapping("x"), Some(&0x0));
         assert_eq!(input.get_key_mapping("t"), None);
     }
+
+    #[test]
+    fn test_get_input_for_key() {
+        let input = Input::default();
+        assert_eq!(input.get_input_for_key(0x0), Some("x"));
+        assert_eq!(input.get_input_for_key(0x10), None);
+    }
 }
\ No newline at end of file
@@ -8,15 +8,33 @@ pub mod opcode;
 /// The registers module contains [`GeneralRegisters`], [`PsuedoRegisters`], and [`SpecialRegisters`] structs and their methods.
 pub mod registers;
 /// The sound module contains the [`Audio`] struct and its methods.
+#[cfg(feature = "sound")]
 pub mod sound;
 /// The input module contains the [`Input`] struct and its methods.
 pub mod input;
+/// The quirks module contains the [`Quirks`] struct.
+pub mod quirks;
+/// The screenshot module encodes the screen as a PNG, see [`Emu::screen_png_bytes`].
+#[cfg(feature = "png")]
+pub mod screenshot;
+/// The rom parser module contains the [`ValidRom`] struct and its methods.
+pub mod rom_parser;
 
 /// width of the CHIP-8 screen
 pub const SCREEN_WIDTH: usize = 64;
 /// height of the CHIP-8 screen
 pub const SCREEN_HEIGHT: usize = 32;
 
+/// Where programs are loaded, and so where the program counter starts.
+/// The memory below it held the original interpreter, and now holds the fonts.
+pub const PROGRAM_START: u16 = 0x200;
+
+/// Every opcode is 2 bytes long, so the program counter moves 2 bytes at a time.
+pub const OPCODE_SIZE: u16 = 2;
+
+/// Each character in `SPRITE_SET` is 5 bytes long.
+pub const FONT_STRIDE: usize = 5;
+
 /// The CHIP-8 CPU has 4096 bytes of memory.
 pub const RAM_SIZE: usize = 4096;
 
@@ -51,5 +69,26 @@ pub const SPRITE_SET: [u8; SPRITE_SET_SIZE] = [
     0xF0, 0x80, 0xF0, 0x80, 0x80, // F
 ];
 
+/// Size of the SUPER-CHIP large character set
+pub const LARGE_SPRITE_SET_SIZE: usize = 100;
+
+/// Where `LARGE_SPRITE_SET` is stored in memory, right after `SPRITE_SET`.
+pub const LARGE_SPRITE_SET_ADDRESS: usize = SPRITE_SET_SIZE;
+
+/// `LARGE_SPRITE_SET` to draw the SUPER-CHIP large digits 0-9
+/// Each character is 10 bytes long so 8 x 10
+pub const LARGE_SPRITE_SET: [u8; LARGE_SPRITE_SET_SIZE] = [
+    0x3C, 0x7E, 0xE7, 0xC3, 0xC3, 0xC3, 0xC3, 0xE7, 0x7E, 0x3C, // 0
+    0x18, 0x38, 0x58, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x3C, // 1
+    0x3E, 0x7F, 0xC3, 0x06, 0x0C, 0x18, 0x30, 0x60, 0xFF, 0xFF, // 2
+    0x3C, 0x7E, 0xC3, 0x03, 0x0E, 0x0E, 0x03, 0xC3, 0x7E, 0x3C, // 3
+    0x06, 0x0E, 0x1E, 0x36, 0x66, 0xC6, 0xFF, 0xFF, 0x06, 0x06, // 4
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFC, 0xFE, 0x03, 0xC3, 0x7E, 0x3C, // 5
+    0x3E, 0x7C, 0xC0, 0xC0, 0xFC, 0xFE, 0xC3, 0xC3, 0x7E, 0x3C, // 6
+    0xFF, 0xFF, 0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x60, 0x60, // 7
+    0x3C, 0x7E, 0xC3, 0xC3, 0x7E, 0x7E, 0xC3, 0xC3, 0x7E, 0x3C, // 8
+    0x3C, 0x7E, 0xC3, 0xC3, 0x7F, 0x3F, 0x03, 0x03, 0x3E, 0x7C, // 9
+];
+
 #[cfg(test)]
 mod opcode_tests;
@@ -4,14 +4,20 @@
 use core::fmt;
 use std::fmt::Display;
 
-use super::emulator::Emu;
+use rand::Rng;
+
+use super::emulator::{DrawMode, Emu, UnknownOpCodePolicy};
+use super::quirks::{JumpQuirk, MemoryQuirk, ShiftQuirk};
+use super::{FONT_STRIDE, LARGE_SPRITE_SET_ADDRESS, RAM_SIZE};
 type Address = u16; // an address
 type Case = u8; // represents a number that can be used in a switch statement
 type Constant = u8; // a 8 bit constant
 type RegisterID = u8; // a 4 bit register number
 
 /// The `OpCodeError` enum represents the different errors that can occur when executing an opcode.
+/// More variants may be added as more interpreters are supported, so matches need a wildcard arm.
 #[derive(Debug, PartialEq)]
+#[non_exhaustive]
 pub enum OpCodeError {
     /// The opcode is invalid.
     InvalidOpCode,
@@ -19,6 +25,13 @@ pub enum OpCodeError {
     DeprecatedOpCode,
     /// Some other error occurred.
     UnknownOpCode,
+    /// The opcode would read or write memory past the end of RAM.
+    OutOfBounds,
+    /// A subroutine was called with every level of the stack in use,
+    /// see [`Emu::set_max_stack_depth`].
+    StackOverflow,
+    /// A subroutine returned with nothing on the stack.
+    StackUnderflow,
 }
 
 impl Display for OpCodeError {
@@ -27,6 +40,9 @@ impl Display for OpCodeError {
             OpCodeError::InvalidOpCode => write!(f, "Invalid opcode"),
             OpCodeError::DeprecatedOpCode => write!(f, "Deprecated opcode"),
             OpCodeError::UnknownOpCode => write!(f, "Unknown opcode"),
+            OpCodeError::OutOfBounds => write!(f, "Memory access out of bounds"),
+            OpCodeError::StackOverflow => write!(f, "Stack overflow"),
+            OpCodeError::StackUnderflow => write!(f, "Stack underflow"),
         }
     }
 }
@@ -74,6 +90,52 @@ pub enum OpCode {
     Unknown,
 }
 
+/// The `OpCategory` enum groups opcodes by what kind of work they do,
+/// e.g. for highlighting a disassembly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpCategory {
+    /// Jumps, calls, returns and conditional skips.
+    FlowControl,
+    /// Loading constants, moving registers and add/subtract.
+    Arithmetic,
+    /// Bitwise operations and shifts.
+    Logic,
+    /// Drawing and clearing the screen.
+    Display,
+    /// Reading and writing the I register and RAM.
+    Memory,
+    /// Reading the keypad.
+    Input,
+    /// Reading and writing the delay and sound timers.
+    Timer,
+    /// Everything else.
+    Misc,
+}
+
+impl OpCode {
+    #[must_use]
+    /// Returns the [`OpCategory`] this opcode belongs to.
+    pub fn category(&self) -> OpCategory {
+        match self {
+            OpCode::Call(_)
+            | OpCode::Return
+            | OpCode::Flow(_, _)
+            | OpCode::SkipEquals(_)
+            | OpCode::SkipRegEquals(_) => OpCategory::FlowControl,
+            OpCode::Constant(_) => OpCategory::Arithmetic,
+            OpCode::BitOp((_, _, case)) => match case {
+                0x1 | 0x2 | 0x3 | 0x6 | 0xE => OpCategory::Logic,
+                _ => OpCategory::Arithmetic,
+            },
+            OpCode::Display(_) => OpCategory::Display,
+            OpCode::IOp(_) | OpCode::MemoryOp(_) | OpCode::Bcd(_) => OpCategory::Memory,
+            OpCode::KeyOpSkip(_, _) | OpCode::KeyOpWait(_) => OpCategory::Input,
+            OpCode::Timer(_) => OpCategory::Timer,
+            OpCode::Nop | OpCode::RandomOp(_) | OpCode::Unknown => OpCategory::Misc,
+        }
+    }
+}
+
 #[allow(clippy::too_many_lines)]
 impl From<u16> for OpCode {
     fn from(value: u16) -> Self {
@@ -102,6 +164,7 @@ impl From<u16> for OpCode {
                 );
                 OpCode::SkipEquals(args)
             }
+            // the last nibble must be 0, so e.g. 0x5121 falls through to Unknown
             (5 | 9, register_x, register_y, 0) => {
                 let args = (
                     u8::try_from(digits.0).expect("Invalid case"),
@@ -177,6 +240,10 @@ impl From<u16> for OpCode {
 
                 OpCode::MemoryOp((reg_id, case))
             }
+            (0xF, reg_id, 3, 0) => {
+                let reg_id = u8::try_from(reg_id).expect("Invalid register number");
+                OpCode::MemoryOp((reg_id, 0x30)) // Fx30, not 30 as that is 0x1E
+            }
             (0xF, reg_id, 3, 3) => {
                 let reg_id = u8::try_from(reg_id).expect("Invalid register number");
                 OpCode::Bcd(reg_id)
@@ -186,23 +253,163 @@ impl From<u16> for OpCode {
     }
 }
 
+#[must_use]
+/// Returns a raw value and the [`OpCode`] it decodes to for every kind of opcode,
+/// e.g. for generating documentation. `Display` is listed twice, for drawing and for clearing.
+pub fn all_opcode_examples() -> Vec<(u16, OpCode)> {
+    vec![
+        (0x0000, OpCode::Nop),
+        (0x0123, OpCode::Call(0x123)),
+        (0x00E0, OpCode::Display(None)),
+        (0xD125, OpCode::Display(Some((1, 2, 5)))),
+        (0x00EE, OpCode::Return),
+        (0x1234, OpCode::Flow(1, 0x234)),
+        (0x3A12, OpCode::SkipEquals((3, 0xA, 0x12))),
+        (0x5AB0, OpCode::SkipRegEquals((5, 0xA, 0xB))),
+        (0x6A12, OpCode::Constant((6, 0xA, 0x12))),
+        (0x8AB4, OpCode::BitOp((0xA, 0xB, 4))),
+        (0xA123, OpCode::IOp(0x123)),
+        (0xFA1E, OpCode::MemoryOp((0xA, 0x1E))),
+        (0xCA12, OpCode::RandomOp((0xA, 0x12))),
+        (0xEA9E, OpCode::KeyOpSkip(0x9E, 0xA)),
+        (0xFA0A, OpCode::KeyOpWait(0xA)),
+        (0xFA15, OpCode::Timer((0xA, 5))),
+        (0xFA33, OpCode::Bcd(0xA)),
+        (0xFFFF, OpCode::Unknown),
+    ]
+}
+
+#[must_use]
+/// Returns a plain English description of what `opcode` does, with its registers and constants
+/// filled in, e.g. "Skip the next instruction if V0 == 0x12". Meant for tooltips and teaching.
+pub fn describe(opcode: &OpCode) -> String {
+    match opcode {
+        OpCode::Nop => "Do nothing".to_string(),
+        OpCode::Call(address) => format!("Call the machine code routine at {address:#05X}"),
+        OpCode::Display(None) => "Clear the screen".to_string(),
+        OpCode::Display(Some((x, y, height))) => format!(
+            "Draw the {height} byte sprite at I at (V{x:X}, V{y:X}), setting VF on a collision"
+        ),
+        OpCode::Return => "Return from the current subroutine".to_string(),
+        OpCode::Flow(case, address) => match case {
+            1 => format!("Jump to {address:#05X}"),
+            2 => format!("Call the subroutine at {address:#05X}"),
+            _ => format!("Jump to {address:#05X} + V0"),
+        },
+        OpCode::SkipEquals((case, register, constant)) => {
+            let comparison = if *case == 3 { "==" } else { "!=" };
+            format!("Skip the next instruction if V{register:X} {comparison} {constant:#04X}")
+        }
+        OpCode::SkipRegEquals((case, register_x, register_y)) => {
+            let comparison = if *case == 5 { "==" } else { "!=" };
+            format!("Skip the next instruction if V{register_x:X} {comparison} V{register_y:X}")
+        }
+        OpCode::Constant((case, register, constant)) => {
+            if *case == 6 {
+                format!("Set V{register:X} to {constant:#04X}")
+            } else {
+                format!("Add {constant:#04X} to V{register:X}, without setting VF")
+            }
+        }
+        OpCode::BitOp((x, y, case)) => match case {
+            0x0 => format!("Set V{x:X} to V{y:X}"),
+            0x1 => format!("Set V{x:X} to V{x:X} OR V{y:X}"),
+            0x2 => format!("Set V{x:X} to V{x:X} AND V{y:X}"),
+            0x3 => format!("Set V{x:X} to V{x:X} XOR V{y:X}"),
+            0x4 => format!("Add V{y:X} to V{x:X}, setting VF on a carry"),
+            0x5 => format!("Subtract V{y:X} from V{x:X}, clearing VF on a borrow"),
+            0x6 => format!("Shift V{x:X} right by 1, setting VF to the bit shifted out"),
+            0x7 => format!("Set V{x:X} to V{y:X} - V{x:X}, clearing VF on a borrow"),
+            0xE => format!("Shift V{x:X} left by 1, setting VF to the bit shifted out"),
+            _ => format!("Unknown operation {case:X} on V{x:X} and V{y:X}"),
+        },
+        OpCode::IOp(address) => format!("Set I to {address:#05X}"),
+        OpCode::MemoryOp((register, case)) => match case {
+            0x1E => format!("Add V{register:X} to I"),
+            29 => format!("Point I at the font sprite for the digit in V{register:X}"),
+            0x30 => format!("Point I at the large font sprite for the digit in V{register:X}"),
+            55 => format!("Store V0 to V{register:X} in memory starting at I"),
+            65 => format!("Load V0 to V{register:X} from memory starting at I"),
+            _ => format!("Unknown memory operation on V{register:X}"),
+        },
+        OpCode::RandomOp((register, constant)) => {
+            format!("Set V{register:X} to a random number AND {constant:#04X}")
+        }
+        OpCode::KeyOpSkip(case, register) => {
+            let state = if *case == 0x9E { "" } else { "not " };
+            format!("Skip the next instruction if the key in V{register:X} is {state}pressed")
+        }
+        OpCode::KeyOpWait(register) => {
+            format!("Wait for a key press and store the key in V{register:X}")
+        }
+        OpCode::Timer((register, case)) => match case {
+            7 => format!("Set V{register:X} to the delay timer"),
+            5 => format!("Set the delay timer to V{register:X}"),
+            _ => format!("Set the sound timer to V{register:X}"),
+        },
+        OpCode::Bcd(register) => {
+            format!("Store the decimal digits of V{register:X} in memory at I, I + 1 and I + 2")
+        }
+        OpCode::Unknown => "Unknown instruction".to_string(),
+    }
+}
+
 impl Emu {
+    /// Reads the raw 2 byte opcode at `address`.
+    pub(crate) fn read_opcode(&self, address: u16) -> u16 {
+        let address = address as usize % RAM_SIZE;
+
+        // An OpCode is 2 bytes long, the second byte wraps to the start of RAM
+        let higher_byte = u16::from(self.ram[address]);
+        let lower_byte = u16::from(self.ram[(address + 1) % RAM_SIZE]);
+        (higher_byte << 8) | lower_byte
+    }
+
     #[must_use]
     /// Fetch the value from our game (loaded into RAM) at the memory address stored in our Program Counter.
     pub(crate) fn fetch_opcode(&mut self) -> OpCode {
-        let pc = self.psuedo_registers.program_counter as usize;
-
-        // An OpCode is 2 bytes long
-        let higher_byte = u16::from(self.ram[pc]);
-        let lower_byte = u16::from(self.ram[pc + 1]);
-        let opcode = (higher_byte << 8) | lower_byte;
+        let opcode = self.read_opcode(self.program_counter());
 
         // increment the program counter by 2
-        self.psuedo_registers.program_counter += 2;
+        self.increment_program_counter();
 
         OpCode::from(opcode)
     }
 
+    /// Decodes the opcode at the program counter without executing it or moving the program
+    /// counter, e.g. to preview the next instruction in a debugger.
+    ///
+    /// # Errors
+    /// Returns [`OpCodeError::UnknownOpCode`] if the bytes at the program counter are not a valid opcode.
+    pub fn peek_opcode(&self) -> Result<OpCode, OpCodeError> {
+        self.opcode_at(self.program_counter())
+    }
+
+    #[must_use]
+    /// Returns the raw two bytes at the program counter as a big-endian `u16`, without moving
+    /// the program counter, e.g. to show `PC: 0xD015` in a debugger.
+    /// Returns `None` if the opcode would run past the end of RAM.
+    pub fn current_opcode_raw(&self) -> Option<u16> {
+        let address = self.program_counter() as usize;
+        let bytes = self.ram.get(address..address + 2)?;
+        Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// Decodes the two bytes at `address` as an opcode, without any side effects,
+    /// e.g. to disassemble memory.
+    ///
+    /// # Arguments
+    /// - `address`: The address of the first byte of the opcode, wrapping around the end of RAM.
+    ///
+    /// # Errors
+    /// Returns [`OpCodeError::UnknownOpCode`] if the bytes at `address` are not a valid opcode.
+    pub fn opcode_at(&self, address: u16) -> Result<OpCode, OpCodeError> {
+        match OpCode::from(self.read_opcode(address)) {
+            OpCode::Unknown => Err(OpCodeError::UnknownOpCode),
+            opcode => Ok(opcode),
+        }
+    }
+
     /// Execute an `OpCode`.
     ///
     /// # Arguments
@@ -218,10 +425,7 @@ impl Emu {
                 self.handle_display(*to_draw);
                 Ok(())
             }
-            OpCode::Return => {
-                self.handle_return();
-                Ok(())
-            } // NOTE: technically a flow instruction
+            OpCode::Return => self.handle_return(), // NOTE: technically a flow instruction
             OpCode::Flow(case, address) => self.handle_flow(*case, *address),
             OpCode::BitOp(args) => self.handle_bit_op(*args),
             OpCode::IOp(address) => {
@@ -239,11 +443,11 @@ impl Emu {
                 self.handle_random_op(*args);
                 Ok(())
             }
-            OpCode::Bcd(reg_id) => {
-                self.handle_bcd(*reg_id);
-                Ok(())
-            }
-            OpCode::Unknown => Err(OpCodeError::UnknownOpCode),
+            OpCode::Bcd(reg_id) => self.handle_bcd(*reg_id),
+            OpCode::Unknown => match self.unknown_opcode_policy {
+                UnknownOpCodePolicy::Error => Err(OpCodeError::UnknownOpCode),
+                UnknownOpCodePolicy::Nop => Ok(()),
+            },
         }
     }
 
@@ -252,7 +456,7 @@ impl Emu {
     ///
     /// # Arguments
     /// - `to_draw`: An optional tuple containing the x, y, and height of the sprite to draw.
-    ///    depending on this, we will either clear or draw
+    ///   depending on this, we will either clear or draw
     ///
     /// Display n-byte sprite starting at memory location I at (Vx, Vy), set VF = collision.
     /// The interpreter reads n bytes from memory, starting at the address stored in I. These bytes are
@@ -262,35 +466,52 @@ impl Emu {
     /// it wraps around to the opposite side of the screen. See instruction 8xy3 for more
     /// information on XOR, and section 2.4, Display, for more information on the Chip-8 screen and
     /// sprites.
+    /// Sprite rows that would be read from past the end of RAM are clipped.
     fn handle_display(&mut self, to_draw: Option<(Constant, Constant, Constant)>) {
         match to_draw {
             Some((reg_x, reg_y, height)) => {
                 let i_reg = self.i_register as usize;
-                let x_val = u16::from(self.get_register_val(reg_x));
-                let y_val = u16::from(self.get_register_val(reg_y));
                 let (screen_width, screen_height) = Self::screen_size();
+                let x_val = usize::from(self.get_register_val(reg_x)) % screen_width;
+                let y_val = usize::from(self.get_register_val(reg_y)) % screen_height;
+
+                let clip_x = self.quirks.clip;
+                let clip_y = self.quirks.clip || !self.quirks.wrap_y;
 
                 let mut collision = false;
-                for row in 0..height.into() {
-                    let sprite = self.ram[i_reg + row as usize];
+                for row in 0..usize::from(height) {
+                    // clip the sprite rather than read past the end of RAM
+                    let Some(&sprite) = self.ram.get(i_reg + row) else {
+                        break;
+                    };
                     for col in 0..8 {
                         // use a mask to fetch current's sprite bit
                         // only flip if a 1
                         if (sprite & (0x80 >> col)) != 0 {
-                            let x = (x_val + col) as usize % screen_width;
-                            let y = (y_val + row) as usize % screen_height;
+                            let (x, y) = (x_val + col, y_val + row);
+                            if (clip_x && x >= screen_width) || (clip_y && y >= screen_height) {
+                                continue;
+                            }
+                            let (x, y) = (x % screen_width, y % screen_height);
 
                             let index = y * screen_width + x;
 
-                            collision |= self.screen[index];
-                            self.screen[index] ^= true;
+                            match self.draw_mode {
+                                DrawMode::Xor => {
+                                    collision |= self.screen[index];
+                                    self.screen[index] ^= true;
+                                }
+                                DrawMode::Or => self.screen[index] = true,
+                            }
                         }
                     }
                 }
                 self.set_register_val(0xF, u8::from(collision));
+                self.waiting_for_frame = self.quirks.display_wait;
             }
             None => self.screen.fill(false),
-        };
+        }
+        self.dirty = true;
     }
 
     #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
@@ -300,7 +521,7 @@ impl Emu {
     /// - `register_id`: The register to act upon.
     ///
     /// Stores the binary-coded decimal representation of VX, with the hundreds digit in memory at location in I, the tens digit at location I+1, and the ones digit at location I+2.[22]
-    fn handle_bcd(&mut self, register_id: RegisterID) {
+    fn handle_bcd(&mut self, register_id: RegisterID) -> Result<(), OpCodeError> {
         let register_val = f32::from(self.get_register_val(register_id));
         let (hundreds, tens, ones) = (
             (register_val / 100.0).floor() as u8,
@@ -309,10 +530,14 @@ impl Emu {
         );
 
         let i_reg = self.i_register as usize;
+        if i_reg + 2 >= RAM_SIZE {
+            return Err(OpCodeError::OutOfBounds);
+        }
 
         self.ram[i_reg] = hundreds;
         self.ram[i_reg + 1] = tens;
         self.ram[i_reg + 2] = ones;
+        Ok(())
     }
 
     /// Handles the `RandomOp` opcode.
@@ -321,7 +546,7 @@ impl Emu {
     /// - `register_id`: The register to act upon.
     /// - `constant`: The constant to act upon.
     fn handle_random_op(&mut self, (register_id, constant): (RegisterID, Constant)) {
-        let random_number: u8 = rand::random();
+        let random_number: u8 = self.rng.gen();
         let result = random_number & constant;
         self.set_register_val(register_id, result);
     }
@@ -343,7 +568,7 @@ impl Emu {
     /// # Cases
     /// - 0x1E: Adds the value of register X to I. VF is not affected.
     /// - 29: Sets I to the location of the sprite for the character in register X. Characters 0-F
-    ///     (in hexadecimal) are represented by a 4x5 font.
+    ///   (in hexadecimal) are represented by a 4x5 font.
     /// - 55: Stores V0 to VX in memory starting at address I. With an offset increment of 1
     /// - 65: Fills V0 to VX with values from memory starting at address I. With an offset increment of 1
     fn handle_memory_op(
@@ -356,27 +581,59 @@ impl Emu {
                 self.i_register = self.i_register.wrapping_add(register_val);
             }
             29 => {
-                let register_val = u16::from(self.get_register_val(register_id));
-                self.i_register = register_val * 5; // each character sprite is 5 bytes long
+                // only the low nibble is a hex digit
+                let digit = usize::from(self.get_register_val(register_id) & 0xF);
+                self.i_register = u16::try_from(digit * FONT_STRIDE).expect("font is in RAM");
+            }
+            0x30 => {
+                // SUPER-CHIP only has large glyphs for 0-9, fall back to the small ones
+                let digit = usize::from(self.get_register_val(register_id) & 0xF);
+                let address = if digit <= 9 {
+                    LARGE_SPRITE_SET_ADDRESS + digit * 10 // each large sprite is 10 bytes long
+                } else {
+                    digit * FONT_STRIDE
+                };
+                self.i_register = u16::try_from(address).expect("font is in RAM");
             }
             55 => {
                 let i_reg = self.i_register as usize;
+                if i_reg + register_id as usize >= RAM_SIZE {
+                    return Err(OpCodeError::OutOfBounds);
+                }
                 for curr_reg in 0..=register_id {
                     self.ram[i_reg + curr_reg as usize] = self.get_register_val(curr_reg);
                 }
+                self.increment_i_after_load_store(register_id);
             }
             65 => {
                 let i_reg = self.i_register as usize;
+                if i_reg + register_id as usize >= RAM_SIZE {
+                    return Err(OpCodeError::OutOfBounds);
+                }
                 for curr_reg in 0..=register_id {
                     let val = self.ram[i_reg + curr_reg as usize];
                     self.set_register_val(curr_reg, val);
                 }
+                self.increment_i_after_load_store(register_id);
             }
             _ => return Err(OpCodeError::InvalidOpCode),
-        };
+        }
         Ok(())
     }
 
+    /// Moves `I` on after `FX55` or `FX65` stored or loaded `V0` to `VX`,
+    /// as [`Quirks::memory`](super::quirks::Quirks::memory) says.
+    /// # Arguments
+    /// - `register_id`: `X`, the last register stored or loaded.
+    fn increment_i_after_load_store(&mut self, register_id: RegisterID) {
+        let increment = match self.quirks.memory {
+            MemoryQuirk::NoIncrement => 0,
+            MemoryQuirk::IncrementByX => u16::from(register_id),
+            MemoryQuirk::IncrementByXPlus1 => u16::from(register_id) + 1,
+        };
+        self.i_register = self.i_register.wrapping_add(increment);
+    }
+
     #[allow(clippy::similar_names)]
     /// Handles the `Assig`,`BitOp`,`Math` opcodes.
     /// Check the case and skips based on the value of a register and a constant.
@@ -392,6 +649,11 @@ impl Emu {
     ) -> Result<(), OpCodeError> {
         let register_x_val = self.get_register_val(register_x);
         let register_y_val = self.get_register_val(register_y);
+        // the value 8XY6 and 8XYE shift
+        let shifted_val = match self.quirks.shift {
+            ShiftQuirk::InPlace => register_x_val,
+            ShiftQuirk::CopyY => register_y_val,
+        };
 
         match case {
             0x0 => {
@@ -401,14 +663,17 @@ impl Emu {
             0x1 => {
                 // Vx |= Vy
                 self.set_register_val(register_x, register_x_val | register_y_val);
+                self.reset_vf_after_logic();
             }
             0x2 => {
                 // Vx &= Vy
                 self.set_register_val(register_x, register_x_val & register_y_val);
+                self.reset_vf_after_logic();
             }
             0x3 => {
                 // Vx ^= Vy
                 self.set_register_val(register_x, register_x_val ^ register_y_val);
+                self.reset_vf_after_logic();
             }
             0x4 => {
                 // Vx += Vy
@@ -425,9 +690,9 @@ impl Emu {
                 self.set_register_val(0xF, u8::from(!overflow));
             }
             0x6 => {
-                // Shift VX right by 1 and stores lsb of VX before shift into VF
-                self.set_register_val(0xF, register_x_val & 0x1);
-                self.set_register_val(register_x, register_x_val >> 1);
+                // Shift VX (or VY) right by 1 and stores lsb before shift into VF
+                self.set_register_val(0xF, shifted_val & 0x1);
+                self.set_register_val(register_x, shifted_val >> 1);
             }
             0x7 => {
                 // Vy -= Vx
@@ -437,15 +702,24 @@ impl Emu {
                 self.set_register_val(0xF, u8::from(!overflow));
             }
             0xE => {
-                // Shift VX left by 1 and stores msb of VX before shift into VF
-                self.set_register_val(0xF, (register_x_val >> 7) & 0x1);
-                self.set_register_val(register_x, register_x_val << 1);
+                // Shift VX (or VY) left by 1 and stores msb before shift into VF
+                self.set_register_val(0xF, (shifted_val >> 7) & 0x1);
+                self.set_register_val(register_x, shifted_val << 1);
             }
             _ => return Err(OpCodeError::InvalidOpCode),
-        };
+        }
         Ok(())
     }
 
+    /// Clears VF after `8XY1`, `8XY2` or `8XY3` with the
+    /// [`Quirks::logic_vf_reset`](super::quirks::Quirks::logic_vf_reset) quirk.
+    /// Done after the result is stored, so VF as `X` still ends up 0.
+    fn reset_vf_after_logic(&mut self) {
+        if self.quirks.logic_vf_reset {
+            self.set_register_val(0xF, 0);
+        }
+    }
+
     /// Handles the `Cond` opcode.
     /// Check the case and skips based on the value of a register and a constant.
     /// # Arguments
@@ -464,8 +738,8 @@ impl Emu {
             _ => return Err(OpCodeError::InvalidOpCode),
         };
         if condition_met {
-            self.psuedo_registers.program_counter += 2;
-        };
+            self.increment_program_counter();
+        }
         Ok(())
     }
 
@@ -488,7 +762,7 @@ impl Emu {
                 self.set_register_val(register, check);
             }
             _ => return Err(OpCodeError::InvalidOpCode),
-        };
+        }
         Ok(())
     }
 
@@ -496,9 +770,10 @@ impl Emu {
     ///
     /// The interpreter sets the program counter to the address at the top of the stack, then
     /// subtracts 1 from the stack pointer.
-    fn handle_return(&mut self) {
-        let return_address = self.pop_stack();
+    fn handle_return(&mut self) -> Result<(), OpCodeError> {
+        let return_address = self.pop_stack()?;
         self.set_program_counter(return_address);
+        Ok(())
     }
 
     /// Handle a flow instruction.
@@ -520,13 +795,18 @@ impl Emu {
             }
             //  The interpreter increments the stack pointer, then puts the current PC on the top of the stack. The PC is then set to nnn.
             2 => {
-                self.push_stack(self.program_counter());
+                self.push_stack(self.program_counter())?;
                 self.set_program_counter(address); // what now? KINDA confused
                 Ok(())
             }
             11 => {
-                let v0 = u16::from(self.get_register_val(0));
-                self.set_program_counter(address + v0);
+                // with the Vx quirk, the high nibble of the address picks the register
+                let register = match self.quirks.jump {
+                    JumpQuirk::V0 => 0,
+                    JumpQuirk::Vx => u8::try_from(address >> 8).expect("address is 12 bits"),
+                };
+                let offset = u16::from(self.get_register_val(register));
+                self.set_program_counter(address + offset);
                 Ok(())
             }
             _ => Err(OpCodeError::InvalidOpCode),
@@ -550,31 +830,27 @@ impl Emu {
             _ => return Err(OpCodeError::InvalidOpCode),
         };
         if skip {
-            self.psuedo_registers.program_counter += 2;
+            self.increment_program_counter();
         }
         Ok(())
     }
 
     /// Handle a keyop wait operation
-    /// Waits for a key press and stores the key in the given register.
+    /// Waits for a key to be pressed and released, and stores the key in the given register.
     /// #Arguments
     /// - `reg_id`: The register to store the key in.
     ///
     /// # Notes
     /// - This is a blocking operation.
-    /// - If multiple keys are pressed, the minimum is chosen.
+    /// - Like the original hardware, the key released is stored, not the lowest one held.
     fn handle_keyop_wait(&mut self, reg_id: u8) {
-        let mut pressed = false;
-        for i in 0..self.keys.len() {
-            if self.keys[i] {
-                self.set_register_val(reg_id, u8::try_from(i).expect("Invalid key"));
-                pressed = true;
-                break;
-            }
-        }
-        if !pressed {
+        if let Some(key) = self.key_released.take() {
+            self.set_register_val(reg_id, u8::try_from(key).expect("Invalid key"));
+            self.awaiting_key = false;
+        } else {
             // Redo opcode
-            self.psuedo_registers.program_counter -= 2;
+            self.decrement_program_counter();
+            self.awaiting_key = true;
         }
     }
 
@@ -587,7 +863,7 @@ impl Emu {
             5 => self.set_delay_timer(self.get_register_val(register_id)),
             8 => self.set_sound_timer(self.get_register_val(register_id)),
             _ => return Err(OpCodeError::InvalidOpCode),
-        };
+        }
         Ok(())
     }
 }
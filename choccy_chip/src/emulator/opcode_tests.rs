@@ -1,6 +1,15 @@
-use super::emulator::Emu;
+use super::emulator::{DrawMode, Emu, EmuError};
+use super::opcode::all_opcode_examples;
+use super::opcode::describe;
+use super::opcode::OpCategory;
 use super::opcode::OpCode;
 use super::opcode::OpCodeError;
+use super::quirks::{JumpQuirk, MemoryQuirk, Quirks, ShiftQuirk};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use super::{
+    FONT_STRIDE, LARGE_SPRITE_SET, PROGRAM_START, RAM_SIZE, SPRITE_SET_SIZE, STACK_SIZE,
+};
 
 fn setup() -> Emu {
     let mut emu = Emu::new();
@@ -8,6 +17,46 @@ fn setup() -> Emu {
     emu
 }
 
+/// Asserts the top left corner of the screen matches `expected`, drawn with `#` for
+/// a lit pixel and `.` for an unlit one. Every pixel outside the art must be unlit.
+fn assert_screen(emu: &Emu, expected: &[&str]) {
+    use std::fmt::Write;
+
+    let (width, height) = Emu::screen_size();
+    let art_width = expected.iter().map(|row| row.len()).max().unwrap_or(0);
+    assert!(
+        expected.len() <= height && art_width <= width,
+        "art is larger than the screen"
+    );
+
+    let actual: Vec<String> = (0..expected.len())
+        .map(|y| {
+            (0..art_width)
+                .map(|x| if emu.screen[y * width + x] { '#' } else { '.' })
+                .collect()
+        })
+        .collect();
+    let matches = expected.iter().zip(&actual).all(|(expected, actual)| {
+        actual.starts_with(expected) && !actual[expected.len()..].contains('#')
+    });
+    let lit_outside = (0..width * height)
+        .filter(|&i| emu.screen[i])
+        .find(|&i| i / width >= expected.len() || i % width >= art_width);
+
+    if !matches || lit_outside.is_some() {
+        let mut message = String::from("screen does not match\n  expected | actual\n");
+        for (expected, actual) in expected.iter().zip(&actual) {
+            let marker = if actual.starts_with(expected) { ' ' } else { '!' };
+            let _ = writeln!(message, "{marker} {expected:art_width$} | {actual}");
+        }
+        if let Some(i) = lit_outside {
+            let (x, y) = (i % width, i / width);
+            let _ = write!(message, "and pixel ({x}, {y}) is lit outside the art");
+        }
+        panic!("{message}");
+    }
+}
+
 #[test]
 fn test_opcode_nop() {
     let mut emu = setup();
@@ -41,7 +90,7 @@ fn test_opcode_call() {
 fn test_opcode_return() {
     let mut emu = setup();
 
-    emu.push_stack(0x200);
+    emu.push_stack(PROGRAM_START).unwrap();
 
     emu.ram[0] = 0x00;
     emu.ram[1] = 0xEE;
@@ -52,7 +101,7 @@ fn test_opcode_return() {
 
     let _ = emu.execute_opcode(&opcode);
 
-    assert_eq!(emu.psuedo_registers.program_counter, 0x200);
+    assert_eq!(emu.psuedo_registers.program_counter, PROGRAM_START);
 }
 
 #[test]
@@ -108,6 +157,24 @@ fn test_opcode_flow_jump_v0() {
     assert_eq!(emu.psuedo_registers.program_counter, 0x357);
 }
 
+#[test]
+fn test_opcode_flow_jump_vx() {
+    let mut emu = setup();
+    emu.set_jump_quirk(JumpQuirk::Vx);
+
+    emu.set_register_val(0, 0x12);
+    emu.set_register_val(3, 0x20);
+
+    emu.ram[0] = 0xB3;
+    emu.ram[1] = 0x45;
+
+    let opcode = emu.fetch_opcode();
+    let _ = emu.execute_opcode(&opcode);
+
+    // 0x345 + V3, not V0
+    assert_eq!(emu.psuedo_registers.program_counter, 0x365);
+}
+
 #[test]
 fn test_opcode_skip_equals() {
     let mut emu = setup();
@@ -181,6 +248,15 @@ fn test_opcode_skip_register_not_equals() {
     assert_eq!(emu.psuedo_registers.program_counter, 4);
 }
 
+#[test]
+fn test_opcode_skip_register_nonzero_last_nibble() {
+    assert_eq!(OpCode::from(0x5120), OpCode::SkipRegEquals((5, 1, 2)));
+    assert_eq!(OpCode::from(0x9120), OpCode::SkipRegEquals((9, 1, 2)));
+    // the decoder is strict, anything but 0 in the last nibble is not a skip
+    assert_eq!(OpCode::from(0x5121), OpCode::Unknown);
+    assert_eq!(OpCode::from(0x912F), OpCode::Unknown);
+}
+
 #[test]
 fn test_opcode_set_const() {
     let mut emu = setup();
@@ -323,6 +399,63 @@ fn test_opcode_bit_ope() {
     assert_eq!(emu.get_register_val(0), 0x24);
 }
 
+#[test]
+fn test_opcode_bit_op_logic_vf_reset() {
+    for case in [0x1, 0x2, 0x3] {
+        let mut emu = setup();
+        emu.set_register_val(0, 0x12);
+        emu.set_register_val(1, 0x34);
+        emu.set_register_val(0xF, 0x1);
+        let _ = emu.execute_opcode(&OpCode::BitOp((0, 1, case)));
+        assert_eq!(emu.get_register_val(0xF), 0x1, "8XY{case:X} without the reset");
+
+        emu.set_logic_vf_reset(true);
+        emu.set_register_val(0, 0x12);
+        let _ = emu.execute_opcode(&OpCode::BitOp((0, 1, case)));
+        assert_eq!(emu.get_register_val(0xF), 0x0, "8XY{case:X} with the reset");
+    }
+
+    // other bit ops set VF themselves
+    let mut emu = setup();
+    emu.set_logic_vf_reset(true);
+    emu.set_register_val(0, 0xFF);
+    emu.set_register_val(1, 0x01);
+    let _ = emu.execute_opcode(&OpCode::BitOp((0, 1, 0x4)));
+    assert_eq!(emu.get_register_val(0xF), 0x1);
+}
+
+#[test]
+fn test_opcode_bit_op_shift_quirk() {
+    // V0 = 0x02, V1 = 0x14, V2 = 0, V0 = V1 >> 1 or V0 >> 1, then draw the digit in V0 at (0, 0)
+    let rom = [
+        0x60, 0x02, 0x61, 0x14, 0x62, 0x00, 0x80, 0x16, 0xF0, 0x29, 0xD2, 0x25,
+    ];
+    let run = |quirk| {
+        let mut emu = Emu::new();
+        emu.set_shift_quirk(quirk);
+        emu.load_rom(&rom).unwrap();
+        for _ in 0..rom.len() / 2 {
+            let opcode = emu.fetch_opcode();
+            emu.execute_opcode(&opcode).unwrap();
+        }
+        emu
+    };
+
+    // 0x14 >> 1 is 0xA
+    let emu = run(ShiftQuirk::CopyY);
+    assert_screen(&emu, &["####", "#..#", "####", "#..#", "#..#"]);
+
+    // 0x02 >> 1 is 0x1
+    let emu = run(ShiftQuirk::InPlace);
+    assert_screen(&emu, &["..#", ".##", "..#", "..#", ".###"]);
+
+    let mut emu = run(ShiftQuirk::CopyY);
+    emu.set_register_val(1, 0x81);
+    let _ = emu.execute_opcode(&OpCode::BitOp((0, 1, 0xE)));
+    assert_eq!(emu.get_register_val(0), 0x02);
+    assert_eq!(emu.get_register_val(0xF), 1);
+}
+
 #[test]
 fn test_opcode_iop() {
     let mut emu = setup();
@@ -381,7 +514,42 @@ fn test_opcode_memory_op29() {
 
     let _ = emu.execute_opcode(&opcode);
 
-    assert_eq!(emu.i_register, 0x5);
+    assert_eq!(emu.i_register as usize, FONT_STRIDE);
+}
+
+#[test]
+fn test_opcode_memory_op29_high_nibble() {
+    let mut emu = setup();
+
+    emu.set_register_val(0, 0x2A);
+
+    let _ = emu.execute_opcode(&OpCode::MemoryOp((0, 29)));
+
+    // the glyph for A, not 0x2A
+    assert_eq!(emu.i_register as usize, 0xA * FONT_STRIDE);
+    assert_eq!(
+        &emu.ram[emu.i_register as usize..][..FONT_STRIDE],
+        &[0xF0, 0x90, 0xF0, 0x90, 0x90]
+    );
+}
+
+#[test]
+fn test_opcode_memory_op30() {
+    let mut emu = setup();
+
+    emu.set_register_val(3, 5);
+
+    emu.ram[0] = 0xF3;
+    emu.ram[1] = 0x30;
+
+    let opcode = emu.fetch_opcode();
+    assert_eq!(opcode, OpCode::MemoryOp((3, 0x30)));
+
+    let _ = emu.execute_opcode(&opcode);
+
+    let glyph = &emu.ram[emu.i_register as usize..][..10];
+    assert_eq!(glyph, &LARGE_SPRITE_SET[50..60]);
+    assert_eq!(glyph[0], 0xFF);
 }
 
 #[test]
@@ -411,6 +579,27 @@ fn test_opcode_memory_op55() {
     assert_eq!(emu.ram[0x37], 0x4);
 }
 
+#[test]
+fn test_opcode_memory_op55_quirk() {
+    for (quirk, expected_i) in [
+        (MemoryQuirk::NoIncrement, 0x34),
+        (MemoryQuirk::IncrementByX, 0x37),
+        (MemoryQuirk::IncrementByXPlus1, 0x38),
+    ] {
+        let mut emu = setup();
+        emu.set_memory_quirk(quirk);
+        emu.i_register = 0x34;
+
+        let _ = emu.execute_opcode(&OpCode::MemoryOp((3, 55)));
+        assert_eq!(emu.i_register, expected_i, "{quirk:?}");
+
+        // FX65 follows the same quirk
+        emu.i_register = 0x34;
+        let _ = emu.execute_opcode(&OpCode::MemoryOp((3, 65)));
+        assert_eq!(emu.i_register, expected_i, "{quirk:?}");
+    }
+}
+
 #[test]
 fn test_opcode_memory_op65() {
     let mut emu = setup();
@@ -544,6 +733,27 @@ fn test_opcode_rand() {
     println!("Register 0: {register_val}");
 }
 
+#[test]
+fn test_opcode_rand_seeded() {
+    let random_op = OpCode::RandomOp((0, 0x0F));
+
+    // the mask is respected whatever the seed
+    for seed in 0..32 {
+        let mut emu = setup();
+        emu.set_rng_seed(seed);
+        emu.execute_opcode(&random_op).unwrap();
+        assert_eq!(emu.get_register_val(0) & 0xF0, 0);
+    }
+
+    // the same seed gives the same numbers
+    let mut expected = StdRng::seed_from_u64(42);
+    let mut emu = Emu::builder().rng_seed(42).build();
+    for _ in 0..8 {
+        emu.execute_opcode(&random_op).unwrap();
+        assert_eq!(emu.get_register_val(0), expected.gen::<u8>() & 0x0F);
+    }
+}
+
 #[test]
 fn test_opcode_display() {
     let mut emu = setup();
@@ -699,3 +909,361 @@ fn wrong_opcode() {
 
     assert_eq!(error, OpCodeError::UnknownOpCode);
 }
+
+#[test]
+fn test_skip_near_end_of_ram() {
+    let mut emu = setup();
+
+    let last_opcode = RAM_SIZE - 2;
+    emu.set_program_counter(u16::try_from(last_opcode).unwrap());
+    emu.set_register_val(0, 0x12);
+
+    emu.ram[last_opcode] = 0x30;
+    emu.ram[last_opcode + 1] = 0x12;
+
+    let opcode = emu.fetch_opcode();
+    assert_eq!(opcode, OpCode::SkipEquals((3, 0, 0x12)));
+    assert_eq!(emu.program_counter(), 0); // wrapped to the start of RAM
+
+    let _ = emu.execute_opcode(&opcode);
+    assert_eq!(emu.program_counter(), 2);
+}
+
+#[test]
+fn test_fetch_last_byte_of_ram() {
+    let mut emu = setup();
+
+    emu.set_program_counter(u16::try_from(RAM_SIZE - 1).unwrap());
+    emu.ram[RAM_SIZE - 1] = 0x60;
+    emu.ram[0] = 0x12; // the second byte wraps around
+
+    let opcode = emu.fetch_opcode();
+    assert_eq!(opcode, OpCode::Constant((6, 0, 0x12)));
+    assert_eq!(emu.program_counter(), 1);
+}
+
+#[test]
+fn test_opcode_category() {
+    assert_eq!(OpCode::Flow(1, 0x200).category(), OpCategory::FlowControl);
+    assert_eq!(OpCode::Return.category(), OpCategory::FlowControl);
+    assert_eq!(OpCode::Display(None).category(), OpCategory::Display);
+    assert_eq!(OpCode::Timer((0, 7)).category(), OpCategory::Timer);
+    assert_eq!(OpCode::KeyOpWait(0).category(), OpCategory::Input);
+
+    // BitOp is split between arithmetic and logic depending on the case
+    assert_eq!(OpCode::BitOp((0, 1, 0x0)).category(), OpCategory::Arithmetic);
+    assert_eq!(OpCode::BitOp((0, 1, 0x1)).category(), OpCategory::Logic);
+    assert_eq!(OpCode::BitOp((0, 1, 0x2)).category(), OpCategory::Logic);
+    assert_eq!(OpCode::BitOp((0, 1, 0x3)).category(), OpCategory::Logic);
+    assert_eq!(OpCode::BitOp((0, 1, 0x4)).category(), OpCategory::Arithmetic);
+    assert_eq!(OpCode::BitOp((0, 1, 0x5)).category(), OpCategory::Arithmetic);
+    assert_eq!(OpCode::BitOp((0, 1, 0x6)).category(), OpCategory::Logic);
+    assert_eq!(OpCode::BitOp((0, 1, 0x7)).category(), OpCategory::Arithmetic);
+    assert_eq!(OpCode::BitOp((0, 1, 0xE)).category(), OpCategory::Logic);
+}
+
+#[test]
+fn test_describe() {
+    assert_eq!(
+        describe(&OpCode::SkipEquals((3, 0, 0x12))),
+        "Skip the next instruction if V0 == 0x12"
+    );
+    assert_eq!(
+        describe(&OpCode::SkipRegEquals((9, 0xA, 0xB))),
+        "Skip the next instruction if VA != VB"
+    );
+    assert_eq!(describe(&OpCode::Constant((6, 3, 0x0F))), "Set V3 to 0x0F");
+    assert_eq!(describe(&OpCode::Flow(1, 0x2A0)), "Jump to 0x2A0");
+    assert_eq!(describe(&OpCode::BitOp((1, 2, 0x2))), "Set V1 to V1 AND V2");
+    assert_eq!(describe(&OpCode::Timer((4, 8))), "Set the sound timer to V4");
+    assert_eq!(describe(&OpCode::Display(None)), "Clear the screen");
+
+    // every kind of opcode has a description
+    for (_, opcode) in all_opcode_examples() {
+        assert!(!describe(&opcode).is_empty());
+    }
+}
+
+#[test]
+fn test_peek_opcode() {
+    let mut emu = setup();
+
+    emu.ram[0] = 0x60;
+    emu.ram[1] = 0x05;
+
+    assert_eq!(emu.peek_opcode(), Ok(OpCode::Constant((6, 0, 0x05))));
+    assert_eq!(emu.program_counter(), 0);
+
+    emu.cycle().unwrap();
+    assert_eq!(emu.program_counter(), 2);
+    assert_eq!(emu.get_register_val(0), 0x05);
+
+    emu.ram[2] = 0xFF;
+    emu.ram[3] = 0xFF;
+    assert_eq!(emu.peek_opcode(), Err(OpCodeError::UnknownOpCode));
+}
+
+#[test]
+fn test_opcode_at() {
+    let mut emu = setup();
+
+    emu.ram[0x300] = 0xA2;
+    emu.ram[0x301] = 0x34;
+    emu.ram[0x302] = 0xFF;
+    emu.ram[0x303] = 0xFF;
+
+    assert_eq!(emu.opcode_at(0x300), Ok(OpCode::IOp(0x234)));
+    assert_eq!(emu.opcode_at(0x302), Err(OpCodeError::UnknownOpCode));
+    assert_eq!(emu.program_counter(), 0);
+}
+
+#[test]
+fn test_opcode_display_sprite_past_end_of_ram() {
+    let mut emu = setup();
+
+    emu.set_register_val(0, 0);
+    emu.i_register = u16::try_from(RAM_SIZE - 2).unwrap();
+    emu.ram[RAM_SIZE - 2] = 0x80;
+    emu.ram[RAM_SIZE - 1] = 0x80;
+
+    // a 15 row sprite, only 2 of which are in RAM
+    let _ = emu.execute_opcode(&OpCode::Display(Some((0, 0, 15))));
+
+    assert_screen(&emu, &["#", "#"]);
+    assert_eq!(emu.get_register_val(0xF), 0);
+}
+
+#[test]
+fn test_opcode_display_clip() {
+    let (width, height) = Emu::screen_size();
+    let mut emu = setup();
+    emu.set_quirks(Quirks {
+        clip: true,
+        ..Quirks::default()
+    });
+
+    // a 4 row sprite drawn 2 rows from the bottom
+    emu.set_register_val(0, 0);
+    emu.set_register_val(1, u8::try_from(height - 2).unwrap());
+    emu.i_register = 0x300;
+    emu.ram[0x300..0x304].fill(0x80);
+
+    // the clipped rows would wrap to the top rows
+    emu.screen[0] = true;
+    emu.screen[width] = true;
+
+    let _ = emu.execute_opcode(&OpCode::Display(Some((0, 1, 4))));
+    assert_eq!(emu.get_register_val(0xF), 0);
+    assert!(emu.screen[(height - 2) * width]);
+    assert!(emu.screen[(height - 1) * width]);
+    assert!(emu.screen[0] && emu.screen[width]);
+
+    // drawing again collides with the visible rows only
+    let _ = emu.execute_opcode(&OpCode::Display(Some((0, 1, 4))));
+    assert_eq!(emu.get_register_val(0xF), 1);
+    assert!(!emu.screen[(height - 1) * width]);
+
+    // wrapping instead, the sprite collides at the top
+    emu.set_quirks(Quirks {
+        wrap_y: true,
+        ..Quirks::default()
+    });
+    let _ = emu.execute_opcode(&OpCode::Display(Some((0, 1, 4))));
+    assert_eq!(emu.get_register_val(0xF), 1);
+    assert!(!emu.screen[0] && !emu.screen[width]);
+}
+
+#[test]
+fn test_all_opcode_examples() {
+    // no wildcard, so a new variant has to be added here, and to the examples
+    fn variant(opcode: &OpCode) -> usize {
+        match opcode {
+            OpCode::Nop => 0,
+            OpCode::Call(_) => 1,
+            OpCode::Display(_) => 2,
+            OpCode::Return => 3,
+            OpCode::Flow(_, _) => 4,
+            OpCode::SkipEquals(_) => 5,
+            OpCode::SkipRegEquals(_) => 6,
+            OpCode::Constant(_) => 7,
+            OpCode::BitOp(_) => 8,
+            OpCode::IOp(_) => 9,
+            OpCode::MemoryOp(_) => 10,
+            OpCode::RandomOp(_) => 11,
+            OpCode::KeyOpSkip(_, _) => 12,
+            OpCode::KeyOpWait(_) => 13,
+            OpCode::Timer(_) => 14,
+            OpCode::Bcd(_) => 15,
+            OpCode::Unknown => 16,
+        }
+    }
+
+    let examples = all_opcode_examples();
+    for (raw, opcode) in &examples {
+        assert_eq!(&OpCode::from(*raw), opcode, "{raw:#06X}");
+    }
+
+    let mut seen: Vec<usize> = examples.iter().map(|(_, opcode)| variant(opcode)).collect();
+    seen.sort_unstable();
+    seen.dedup();
+    assert_eq!(seen, (0..=16).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_opcode_display_or_mode() {
+    let mut emu = setup();
+    emu.set_draw_mode(DrawMode::Or);
+
+    emu.set_register_val(0, 0);
+    emu.i_register = 0x300;
+    emu.ram[0x300] = 0xC0;
+
+    let _ = emu.execute_opcode(&OpCode::Display(Some((0, 0, 1))));
+    let _ = emu.execute_opcode(&OpCode::Display(Some((0, 0, 1))));
+
+    assert_screen(&emu, &["##"]);
+    assert_eq!(emu.get_register_val(0xF), 0);
+}
+
+#[test]
+fn test_opcode_display_wrap() {
+    let (width, height) = Emu::screen_size();
+    let draw_at = |quirks: Quirks, x: usize, y: usize| {
+        let mut emu = setup();
+        emu.set_quirks(quirks);
+        emu.set_register_val(0, u8::try_from(x).unwrap());
+        emu.set_register_val(1, u8::try_from(y).unwrap());
+        // a 4 pixel wide, 2 row sprite
+        emu.i_register = 0x300;
+        emu.ram[0x300..0x302].fill(0xF0);
+        let _ = emu.execute_opcode(&OpCode::Display(Some((0, 1, 2))));
+        (0..emu.screen.len())
+            .filter(|&i| emu.screen[i])
+            .map(|i| (i % width, i / width))
+            .collect::<Vec<_>>()
+    };
+    let wrap_y = Quirks {
+        wrap_y: true,
+        ..Quirks::default()
+    };
+
+    // across the right edge: wraps to the left edge, with or without wrap_y
+    for quirks in [Quirks::default(), wrap_y] {
+        let lit = draw_at(quirks, width - 2, 0);
+        assert_eq!(
+            lit,
+            vec![(0, 0), (1, 0), (62, 0), (63, 0), (0, 1), (1, 1), (62, 1), (63, 1)]
+        );
+    }
+
+    // across the bottom edge: cut off by default, wraps to the top with wrap_y
+    let lit = draw_at(Quirks::default(), 0, height - 1);
+    assert_eq!(lit, vec![(0, 31), (1, 31), (2, 31), (3, 31)]);
+    let lit = draw_at(wrap_y, 0, height - 1);
+    assert_eq!(
+        lit,
+        vec![(0, 0), (1, 0), (2, 0), (3, 0), (0, 31), (1, 31), (2, 31), (3, 31)]
+    );
+}
+
+#[test]
+fn test_opcode_call_stack_overflow() {
+    let mut emu = setup();
+
+    for _ in 0..STACK_SIZE {
+        emu.execute_opcode(&OpCode::Flow(2, 0x300)).unwrap();
+    }
+    let error = emu.execute_opcode(&OpCode::Flow(2, 0x300)).unwrap_err();
+    assert_eq!(error, OpCodeError::StackOverflow);
+}
+
+#[test]
+fn test_opcode_return_stack_underflow() {
+    let mut emu = setup();
+
+    let error = emu.execute_opcode(&OpCode::Return).unwrap_err();
+    assert_eq!(error, OpCodeError::StackUnderflow);
+    assert_eq!(emu.program_counter(), 0);
+}
+
+#[test]
+fn test_opcode_memory_out_of_bounds() {
+    let mut emu = setup();
+    emu.i_register = u16::try_from(RAM_SIZE - 2).unwrap();
+
+    // V0 and V1 fit, V2 does not
+    emu.execute_opcode(&OpCode::MemoryOp((1, 55))).unwrap();
+    let error = emu.execute_opcode(&OpCode::MemoryOp((2, 55))).unwrap_err();
+    assert_eq!(error, OpCodeError::OutOfBounds);
+    let error = emu.execute_opcode(&OpCode::MemoryOp((2, 65))).unwrap_err();
+    assert_eq!(error, OpCodeError::OutOfBounds);
+
+    // the ones digit would be past the end
+    let error = emu.execute_opcode(&OpCode::Bcd(0)).unwrap_err();
+    assert_eq!(error, OpCodeError::OutOfBounds);
+}
+
+#[test]
+fn test_opcode_bcd_at_ram_end() {
+    let mut emu = setup();
+    emu.set_register_val(0, 123);
+
+    // only the hundreds digit would fit, nothing is written
+    emu.i_register = u16::try_from(RAM_SIZE - 1).unwrap();
+    let error = emu.execute_opcode(&OpCode::Bcd(0)).unwrap_err();
+    assert_eq!(error, OpCodeError::OutOfBounds);
+    assert_eq!(emu.ram[RAM_SIZE - 1], 0);
+
+    // all three digits just fit
+    emu.i_register = u16::try_from(RAM_SIZE - 3).unwrap();
+    emu.execute_opcode(&OpCode::Bcd(0)).unwrap();
+    assert_eq!(emu.ram[RAM_SIZE - 3..], [1, 2, 3]);
+}
+
+#[test]
+fn test_current_opcode_raw() {
+    let mut emu = setup();
+    emu.set_program_counter(0x300);
+    emu.ram[0x300] = 0xD0;
+    emu.ram[0x301] = 0x15;
+
+    assert_eq!(emu.current_opcode_raw(), Some(0xD015));
+    assert_eq!(emu.program_counter(), 0x300);
+
+    emu.set_program_counter(u16::try_from(RAM_SIZE - 1).unwrap());
+    assert_eq!(emu.current_opcode_raw(), None);
+}
+
+#[test]
+fn test_opcode_clear_screen() {
+    let mut emu = setup();
+    emu.screen.fill(true);
+
+    emu.execute_opcode(&OpCode::Display(None)).unwrap();
+
+    assert_screen(&emu, &[]);
+    assert!(emu.take_dirty());
+}
+
+#[test]
+fn test_custom_font() {
+    // every character is a filled block
+    let font = [0xFF; SPRITE_SET_SIZE];
+    let mut emu = Emu::builder().font(&font).unwrap().build();
+    emu.load_rom(&[
+        0x60, 0x01, // V0 = 1
+        0x61, 0x00, // V1 = 0
+        0xF0, 0x29, // I = the sprite for V0
+        0xD1, 0x15, // draw it at (V1, V1)
+        0x12, 0x08, // jump to self
+    ])
+    .unwrap();
+
+    emu.run_until_display().unwrap();
+    assert_screen(&emu, &["########"; 5]);
+
+    assert_eq!(
+        Emu::builder().font(&font[1..]).unwrap_err(),
+        EmuError::InvalidFont(SPRITE_SET_SIZE - 1)
+    );
+}
@@ -0,0 +1,123 @@
+//! This module contains the `Quirks` struct, the behaviours that differ between CHIP-8 interpreters.
+//! ROMs written for one interpreter may rely on its quirks, so they can be toggled per ROM.
+//! Some quirks contradict each other, see [`Quirks::validate`].
+use core::fmt;
+use std::fmt::Display;
+
+/// The `Quirks` struct selects which interpreter behaviours the emulator follows.
+/// The default has every quirk turned off.
+/// With the `serde` feature, it can be saved in a frontend's settings, any quirk left out is off.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Quirks {
+    /// Drawing a sprite waits for the start of the next frame before running any more
+    /// instructions, as the original COSMAC VIP waited for the vertical blank interrupt.
+    /// This limits programs to one sprite drawn per frame.
+    pub display_wait: bool,
+    /// Sprites drawn past the right or bottom edge of the screen are cut off, rather than
+    /// wrapping around to the other side. Clipped pixels never count as a collision.
+    /// The position a sprite starts drawing at always wraps.
+    pub clip: bool,
+    /// Without `clip`, sprites drawn past the right edge wrap around to the left edge,
+    /// but are cut off at the bottom edge, as on the original hardware.
+    /// With `wrap_y` they wrap from the bottom edge to the top too, as some interpreters do.
+    /// Has no effect with `clip`, which cuts sprites off at both edges, so the two are
+    /// rejected together by [`Quirks::validate`].
+    pub wrap_y: bool,
+    /// Which register `8XY6` and `8XYE` shift.
+    pub shift: ShiftQuirk,
+    /// What `FX55` and `FX65` leave in `I`.
+    pub memory: MemoryQuirk,
+    /// Which register `BNNN` adds to the address it jumps to.
+    pub jump: JumpQuirk,
+    /// `8XY1`, `8XY2` and `8XY3` (OR, AND and XOR) clear VF after storing their result,
+    /// as on the original COSMAC VIP. Without it VF is left alone.
+    pub logic_vf_reset: bool,
+}
+
+/// The `ShiftQuirk` enum represents which register `8XY6` and `8XYE` shift.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum ShiftQuirk {
+    /// `VX` is shifted in place and `VY` is ignored, as on the CHIP-48 and SUPER-CHIP.
+    #[default]
+    InPlace,
+    /// `VY` is copied into `VX`, then shifted, as on the original COSMAC VIP.
+    CopyY,
+}
+
+/// The `MemoryQuirk` enum represents what `FX55` and `FX65` leave in `I` after storing or
+/// loading `V0` to `VX`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum MemoryQuirk {
+    /// `I` is left alone, as on the SUPER-CHIP.
+    #[default]
+    NoIncrement,
+    /// `I` is increased by `X`, as on the CHIP-48.
+    IncrementByX,
+    /// `I` is increased by `X + 1`, pointing past the last byte, as on the original COSMAC VIP.
+    IncrementByXPlus1,
+}
+
+impl Quirks {
+    /// Checks that the quirks can all be followed at once.
+    /// [`EmuBuilder::quirks`](super::emulator::EmuBuilder::quirks) calls this.
+    ///
+    /// # Errors
+    /// Returns a [`QuirksError`] naming the first contradiction found.
+    pub fn validate(&self) -> Result<(), QuirksError> {
+        if self.clip && self.wrap_y {
+            return Err(QuirksError::ClipAndWrapY);
+        }
+        Ok(())
+    }
+}
+
+/// The `JumpQuirk` enum represents which register `BNNN` adds to the address it jumps to.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum JumpQuirk {
+    /// `BNNN` jumps to `NNN + V0`, as on the original COSMAC VIP.
+    #[default]
+    V0,
+    /// `BXNN` jumps to `XNN + VX`, the high nibble of the address picking the register,
+    /// as on the CHIP-48 and SUPER-CHIP.
+    Vx,
+}
+
+/// The `QuirksError` enum represents the combinations of quirks that contradict each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuirksError {
+    /// `clip` cuts sprites off at the bottom edge, where `wrap_y` wraps them to the top.
+    ClipAndWrapY,
+}
+
+impl Display for QuirksError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuirksError::ClipAndWrapY => {
+                write!(f, "the clip and wrap_y quirks contradict each other")
+            }
+        }
+    }
+}
+
+impl std::error::Error for QuirksError {}
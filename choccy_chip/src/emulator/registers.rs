@@ -1,7 +1,7 @@
 #![allow(dead_code)]
 //! This module contains the registers struct and its methods for the CHIP-8 CPU.
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 #[allow(clippy::module_name_repetitions)]
 /// `PsuedoRegisters` are registers that are not accessible to programs but the emulator
 pub struct PsuedoRegisters {
@@ -11,7 +11,7 @@ pub struct PsuedoRegisters {
     pub(crate) stack_pointer: u8,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 #[allow(clippy::module_name_repetitions)]
 /// `SpecialRegisters` struct contains the delay and sound timers.
 pub struct SpecialRegisters {
@@ -21,7 +21,7 @@ pub struct SpecialRegisters {
     pub(crate) sound_timer: u8,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 #[allow(clippy::module_name_repetitions)]
 /// `GeneralRegisters` struct contains the 16 general purpose registers.
 /// They are named V0, V1, ..., VE, VF.
@@ -35,15 +35,16 @@ pub struct GeneralRegisters {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::emulator::PROGRAM_START;
 
     #[test]
     fn test_pseudo_registers() {
         let psuedo_registers = PsuedoRegisters {
-            program_counter: 0x200,
+            program_counter: PROGRAM_START,
             stack_pointer: 0,
         };
 
-        assert_eq!(psuedo_registers.program_counter, 0x200);
+        assert_eq!(psuedo_registers.program_counter, PROGRAM_START);
         assert_eq!(psuedo_registers.stack_pointer, 0);
     }
 
@@ -0,0 +1,252 @@
+//! This module contains the `ValidRom` struct, a ROM that has been checked to fit in memory.
+//! Additionally, it contains the `RomError` enum which represents the ways a ROM can be invalid,
+//! and [`detect_variant`] which guesses which interpreter a ROM was written for.
+//! With the `flate2` feature, gzipped ROMs are decompressed before they are validated.
+use core::fmt;
+#[cfg(feature = "flate2")]
+use std::borrow::Cow;
+use std::fmt::Display;
+#[cfg(feature = "flate2")]
+use std::io::Read;
+
+use super::emulator::Emu;
+use super::quirks::{JumpQuirk, Quirks};
+use super::RAM_SIZE;
+
+/// The `RomError` enum represents the different reasons a ROM can fail validation.
+/// Features can add variants, e.g. `flate2` adds `InvalidGzip`, so matches need a
+/// wildcard arm.
+#[derive(Debug, PartialEq)]
+#[non_exhaustive]
+pub enum RomError {
+    /// The ROM is smaller than a single opcode.
+    TooSmall,
+    /// The ROM does not fit in memory after the start address.
+    TooLarge,
+    /// The ROM starts like a gzip file, but could not be decompressed.
+    #[cfg(feature = "flate2")]
+    InvalidGzip,
+}
+
+impl Display for RomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RomError::TooSmall => write!(f, "ROM is smaller than a single opcode"),
+            RomError::TooLarge => write!(f, "ROM is too large to fit in memory"),
+            #[cfg(feature = "flate2")]
+            RomError::InvalidGzip => write!(f, "ROM looks gzipped but could not be decompressed"),
+        }
+    }
+}
+
+impl std::error::Error for RomError {}
+
+/// Checks that a ROM holds at least one opcode and fits in memory when loaded at `start`.
+///
+/// # Errors
+/// Returns [`RomError::TooSmall`] or [`RomError::TooLarge`] if the ROM is invalid.
+pub fn validate_rom(bytes: &[u8], start: u16) -> Result<(), RomError> {
+    if bytes.len() < 2 {
+        return Err(RomError::TooSmall);
+    }
+    if start as usize + bytes.len() > RAM_SIZE {
+        return Err(RomError::TooLarge);
+    }
+    Ok(())
+}
+
+/// The first two bytes of every gzip file.
+#[cfg(feature = "flate2")]
+const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
+
+/// Decompresses `bytes` if they start with the gzip magic bytes, otherwise returns them as is.
+///
+/// # Errors
+/// Returns [`RomError::InvalidGzip`] if the gzip data is corrupt.
+#[cfg(feature = "flate2")]
+fn decompress(bytes: &[u8]) -> Result<Cow<'_, [u8]>, RomError> {
+    if !bytes.starts_with(&GZIP_MAGIC) {
+        return Ok(Cow::Borrowed(bytes));
+    }
+    let mut decompressed = Vec::new();
+    flate2::read::GzDecoder::new(bytes)
+        .read_to_end(&mut decompressed)
+        .map_err(|_| RomError::InvalidGzip)?;
+    Ok(Cow::Owned(decompressed))
+}
+
+/// A `ValidRom` is a ROM that is known to fit in memory at its start address.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidRom {
+    bytes: Vec<u8>,
+    start: u16,
+}
+
+impl ValidRom {
+    /// Validates a ROM that will be loaded at `start`.
+    /// With the `flate2` feature, a gzipped ROM is decompressed first.
+    ///
+    /// # Errors
+    /// Returns a [`RomError`] if the ROM fails [`validate_rom`].
+    pub fn from_bytes(bytes: &[u8], start: u16) -> Result<Self, RomError> {
+        #[cfg(feature = "flate2")]
+        let bytes = &*decompress(bytes)?;
+        validate_rom(bytes, start)?;
+        Ok(Self {
+            bytes: bytes.to_vec(),
+            start,
+        })
+    }
+
+    #[must_use]
+    /// Returns the bytes of the ROM.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    #[must_use]
+    /// Returns the address the ROM is loaded at.
+    pub fn start(&self) -> u16 {
+        self.start
+    }
+}
+
+impl TryFrom<&[u8]> for ValidRom {
+    type Error = RomError;
+
+    /// Validates a ROM that will be loaded at the default start address, 0x200.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_bytes(bytes, Emu::START_ADDRESS)
+    }
+}
+
+/// The `Chip8Variant` enum represents the interpreters a ROM may have been written for.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Chip8Variant {
+    /// The original CHIP-8.
+    #[default]
+    Chip8,
+    /// SUPER-CHIP, which adds a high resolution mode, scrolling and larger sprites.
+    SuperChip,
+}
+
+impl Display for Chip8Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Chip8Variant::Chip8 => write!(f, "CHIP-8"),
+            Chip8Variant::SuperChip => write!(f, "SUPER-CHIP"),
+        }
+    }
+}
+
+impl Chip8Variant {
+    #[must_use]
+    /// Returns the quirks ROMs written for this variant expect.
+    pub fn quirks(self) -> Quirks {
+        match self {
+            Chip8Variant::Chip8 => Quirks::default(),
+            Chip8Variant::SuperChip => Quirks {
+                clip: true,
+                jump: JumpQuirk::Vx,
+                ..Quirks::default()
+            },
+        }
+    }
+}
+
+/// Guesses which interpreter a ROM was written for, by looking for opcodes only that
+/// interpreter has. Data in the ROM may look like such an opcode, so this is only a guess.
+#[must_use]
+pub fn detect_variant(bytes: &[u8]) -> Chip8Variant {
+    let is_super_chip_opcode = |opcode: u16| match opcode & 0xF000 {
+        // scroll down, scroll right/left, exit, low/high resolution
+        0x0000 => opcode & 0xFFF0 == 0x00C0 || (0x00FB..=0x00FF).contains(&opcode),
+        // a 16x16 sprite
+        0xD000 => opcode.trailing_zeros() >= 4,
+        // large font, save/load flags
+        0xF000 => matches!(opcode & 0x00FF, 0x30 | 0x75 | 0x85),
+        _ => false,
+    };
+
+    let super_chip = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+        .any(is_super_chip_opcode);
+
+    if super_chip {
+        Chip8Variant::SuperChip
+    } else {
+        Chip8Variant::Chip8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_rom() {
+        assert_eq!(validate_rom(&[0x00, 0xE0], 0x200), Ok(()));
+        assert_eq!(validate_rom(&[0x00], 0x200), Err(RomError::TooSmall));
+        assert_eq!(
+            validate_rom(&[0; RAM_SIZE - 0x200 + 1], 0x200),
+            Err(RomError::TooLarge)
+        );
+    }
+
+    #[test]
+    fn test_try_from() {
+        let rom = ValidRom::try_from(&[0x00, 0xE0][..]).unwrap();
+        assert_eq!(rom.bytes(), &[0x00, 0xE0]);
+        assert_eq!(rom.start(), 0x200);
+
+        let too_large = [0; RAM_SIZE];
+        assert_eq!(
+            ValidRom::try_from(&too_large[..]),
+            Err(RomError::TooLarge)
+        );
+    }
+
+    #[cfg(feature = "flate2")]
+    #[test]
+    fn test_gzipped_rom() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        // set V0 to 5, then jump to itself
+        let rom = [0x60, 0x05, 0x12, 0x02];
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&rom).unwrap();
+        let gzipped = encoder.finish().unwrap();
+
+        let valid = ValidRom::try_from(&gzipped[..]).unwrap();
+        assert_eq!(valid.bytes(), &rom);
+
+        let mut emu = Emu::new();
+        emu.load_validated(&valid, false);
+        emu.cycle().unwrap();
+        assert_eq!(emu.registers()[0], 5);
+
+        assert_eq!(
+            ValidRom::try_from(&gzipped[..4]),
+            Err(RomError::InvalidGzip)
+        );
+    }
+
+    #[test]
+    fn test_detect_variant() {
+        // clear the screen, then jump to itself
+        assert_eq!(detect_variant(&[0x00, 0xE0, 0x12, 0x02]), Chip8Variant::Chip8);
+        // switch to high resolution
+        assert_eq!(
+            detect_variant(&[0x00, 0xFF, 0x12, 0x02]),
+            Chip8Variant::SuperChip
+        );
+        // a 16x16 sprite
+        assert_eq!(
+            detect_variant(&[0xD0, 0x10, 0x12, 0x02]),
+            Chip8Variant::SuperChip
+        );
+    }
+}
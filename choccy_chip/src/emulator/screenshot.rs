@@ -0,0 +1,60 @@
+//! This module encodes the screen of the `Emu` as an image, for frontends that want to
+//! save or send frames rather than draw them.
+use super::emulator::Emu;
+
+impl Emu {
+    #[must_use]
+    /// Encodes the screen as a black and white PNG, each pixel `scale` pixels wide,
+    /// e.g. to send a frame over a socket.
+    ///
+    /// # Arguments
+    /// * `scale`: how many image pixels wide each CHIP-8 pixel is, at least 1.
+    ///
+    /// # Panics
+    /// Panics if the image is too large to encode.
+    pub fn screen_png_bytes(&self, scale: u32) -> Vec<u8> {
+        let scale = scale.max(1) as usize;
+        let (width, height) = Self::screen_size();
+
+        let mut pixels = Vec::with_capacity(width * height * scale * scale);
+        for row in self.screen_rows() {
+            let scaled_row: Vec<u8> = row
+                .iter()
+                .flat_map(|&on| std::iter::repeat_n(if on { 0xFF } else { 0x00 }, scale))
+                .collect();
+            for _ in 0..scale {
+                pixels.extend_from_slice(&scaled_row);
+            }
+        }
+
+        let mut bytes = Vec::new();
+        let image_width = u32::try_from(width * scale).expect("image too wide");
+        let image_height = u32::try_from(height * scale).expect("image too tall");
+        let mut encoder = png::Encoder::new(&mut bytes, image_width, image_height);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        // writing to a Vec cannot fail
+        let mut writer = encoder.write_header().expect("failed to write PNG header");
+        writer
+            .write_image_data(&pixels)
+            .expect("failed to write PNG data");
+        writer.finish().expect("failed to finish PNG");
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_screen_png_bytes() {
+        let mut emu = Emu::new();
+        emu.screen[0] = true;
+
+        let bytes = emu.screen_png_bytes(2);
+        assert!(bytes.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]));
+        // the IHDR chunk holds the width and height
+        assert_eq!(&bytes[16..24], &[0, 0, 0, 128, 0, 0, 0, 64]);
+    }
+}
@@ -1,5 +1,5 @@
 /// The `Sound` struct is used to play audio in the CHIP-8 emulator.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct Audio {
 }
 
@@ -1,9 +1,21 @@
 #![warn(missing_docs, missing_debug_implementations, rust_2018_idioms)]
 #![warn(clippy::pedantic)]
 //! Choccy Chip is a CHIP-8 emulator written in Rust.
+//!
+//! The ROM types are re-exported here and in the [`prelude`], so they can be used without
+//! knowing they live in `emulator::rom_parser`:
+//! ```
+//! let rom = choccy_chip::ValidRom::try_from(&[0x00, 0xE0][..]).unwrap();
+//! assert_eq!(rom.start(), 0x200);
+//!
+//! let too_small = choccy_chip::ValidRom::try_from(&[0x00][..]);
+//! assert_eq!(too_small, Err(choccy_chip::RomError::TooSmall));
+//! ```
 
 pub mod prelude;
 pub mod emulator;
+
+pub use emulator::rom_parser::{detect_variant, Chip8Variant, RomError, ValidRom};
 // /// Input API
 // pub mod input;
 // /// Audio API
@@ -5,6 +5,15 @@
 //! ```
 //! use choccy_chip::prelude::*;
 //! ```
-pub use crate::emulator::emulator::Emu;
-pub use crate::emulator::opcode::OpCode;
-pub use crate::emulator::{SCREEN_HEIGHT, SCREEN_WIDTH, SPRITE_SET_SIZE, SPRITE_SET};
+pub use crate::emulator::emulator::{
+    BatchResult, CycleOutcome, DrawMode, Emu, EmuBuilder, EmuError, EmuWarning, FrameEvents,
+    ScreenTransform, ScreenView, ScreenViewIter, UnknownOpCodePolicy,
+};
+pub use crate::emulator::input::{Input, InputEvent};
+pub use crate::emulator::opcode::{OpCategory, OpCode};
+pub use crate::emulator::quirks::{JumpQuirk, MemoryQuirk, Quirks, QuirksError, ShiftQuirk};
+pub use crate::emulator::rom_parser::{detect_variant, Chip8Variant, RomError, ValidRom};
+pub use crate::emulator::{
+    FONT_STRIDE, LARGE_SPRITE_SET, LARGE_SPRITE_SET_SIZE, OPCODE_SIZE, PROGRAM_START,
+    SCREEN_HEIGHT, SCREEN_WIDTH, SPRITE_SET, SPRITE_SET_SIZE,
+};
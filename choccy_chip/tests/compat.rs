@@ -0,0 +1,118 @@
+//! Runs every ROM fixture in `tests/compat` and checks the screen it leaves behind.
+//!
+//! A fixture is a text file of `key = value` lines, `#` starting a comment:
+//! - `rom`: the ROM as hex bytes, separated by spaces.
+//! - `quirks`: the quirks to turn on, separated by commas, e.g. `clip, wrap_y`. May be empty.
+//! - `seed`: the seed for the random numbers of `CXNN`.
+//! - `hash`: the [`screen_hash`] the ROM should leave behind, in hex.
+//!
+//! To add a fixture, write it with any hash and take the real one from the failure message.
+use std::fs;
+use std::path::Path;
+
+use choccy_chip::prelude::*;
+
+/// The most instructions a fixture may run before it must have halted.
+const MAX_CYCLES: usize = 10_000;
+
+/// A ROM, how to run it, and the screen it should leave behind.
+#[derive(Debug)]
+struct Fixture {
+    rom: Vec<u8>,
+    quirks: Quirks,
+    seed: u64,
+    hash: u64,
+}
+
+/// Parses a fixture file, panicking with `name` on anything malformed.
+fn parse_fixture(name: &str, contents: &str) -> Fixture {
+    let mut fixture = Fixture {
+        rom: Vec::new(),
+        quirks: Quirks::default(),
+        seed: 0,
+        hash: 0,
+    };
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .unwrap_or_else(|| panic!("{name}: expected `key = value`, got `{line}`"));
+        let value = value.trim();
+        match key.trim() {
+            "rom" => {
+                fixture.rom = value
+                    .split_whitespace()
+                    .map(|byte| u8::from_str_radix(byte, 16))
+                    .collect::<Result<_, _>>()
+                    .unwrap_or_else(|err| panic!("{name}: bad rom byte: {err}"));
+            }
+            "quirks" => {
+                for quirk in value.split(',').map(str::trim).filter(|q| !q.is_empty()) {
+                    match quirk {
+                        "display_wait" => fixture.quirks.display_wait = true,
+                        "clip" => fixture.quirks.clip = true,
+                        "wrap_y" => fixture.quirks.wrap_y = true,
+                        _ => panic!("{name}: unknown quirk `{quirk}`"),
+                    }
+                }
+            }
+            "seed" => fixture.seed = value.parse().expect("seed should be a number"),
+            "hash" => {
+                let hex = value.trim_start_matches("0x");
+                fixture.hash = u64::from_str_radix(hex, 16).expect("hash should be hex");
+            }
+            key => panic!("{name}: unknown key `{key}`"),
+        }
+    }
+    fixture
+}
+
+/// A 64-bit FNV-1a hash of the screen, one byte per pixel, row by row.
+/// Unlike the standard library's hasher, this is the same on every Rust version.
+fn screen_hash(emu: &Emu) -> u64 {
+    emu.screen_rows()
+        .flatten()
+        .fold(0xCBF2_9CE4_8422_2325, |hash, &on| {
+            (hash ^ u64::from(on)).wrapping_mul(0x0100_0000_01B3)
+        })
+}
+
+/// Runs the fixture's ROM until it halts, ticking the timers whenever it waits for a frame.
+fn run(fixture: &Fixture) -> Emu {
+    let mut emu = Emu::builder()
+        .quirks(fixture.quirks)
+        .unwrap()
+        .rng_seed(fixture.seed)
+        .build();
+    emu.load_rom(&fixture.rom).unwrap();
+
+    for _ in 0..MAX_CYCLES {
+        match emu.cycle().unwrap() {
+            CycleOutcome::Halted => return emu,
+            CycleOutcome::WaitingForFrame => emu.tick_timers(),
+            CycleOutcome::Executed | CycleOutcome::WaitingForKey => {}
+        }
+    }
+    panic!("the ROM did not halt within {MAX_CYCLES} instructions");
+}
+
+#[test]
+fn test_compat_fixtures() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/compat");
+    let mut paths: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .collect();
+    paths.sort();
+    assert!(paths.len() >= 2, "expected fixtures in {}", dir.display());
+
+    for path in paths {
+        let name = path.file_name().unwrap().to_string_lossy();
+        let fixture = parse_fixture(&name, &fs::read_to_string(&path).unwrap());
+        let hash = screen_hash(&run(&fixture));
+        assert_eq!(hash, fixture.hash, "{name}: the screen hash is {hash:#018x}");
+    }
+}
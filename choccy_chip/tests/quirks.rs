@@ -0,0 +1,139 @@
+//! Runs a hand-written ROM that probes the behaviours CHIP-8 interpreters disagree on,
+//! and checks the registers it leaves behind for each emulator configuration.
+use choccy_chip::prelude::*;
+
+/// Each probe stores what it observed in a register, then the ROM halts.
+#[rustfmt::skip]
+const QUIRKS_ROM: [u8; 46] = [
+    // VF reset: does 8XY1 clear VF? VE = VF afterwards
+    0x6F, 0x55, // 0x200: VF = 0x55
+    0x60, 0x0F, // 0x202: V0 = 0x0F
+    0x61, 0xF0, // 0x204: V1 = 0xF0
+    0x80, 0x11, // 0x206: V0 |= V1
+    0x8E, 0xF0, // 0x208: VE = VF
+    // shift: does 8XY6 shift VX or VY? VD = VF (the bit shifted out)
+    0x62, 0x04, // 0x20A: V2 = 0x04
+    0x63, 0x81, // 0x20C: V3 = 0x81
+    0x82, 0x36, // 0x20E: V2 = V2 >> 1 or V3 >> 1
+    0x8D, 0xF0, // 0x210: VD = VF
+    // load/store: does FX55 move I? V4 = what FX65 reads back
+    0xA3, 0x00, // 0x212: I = 0x300
+    0xF1, 0x55, // 0x214: store V0 and V1 at I
+    0xF0, 0x65, // 0x216: V0 = RAM[I]
+    0x84, 0x00, // 0x218: V4 = V0
+    // jump: does BNNN add V0 or VX? V5 = 1 for V0, 2 for VX
+    0x60, 0x04, // 0x21A: V0 = 4
+    0x62, 0x00, // 0x21C: V2 = 0
+    0xB2, 0x24, // 0x21E: jump to 0x224 + V0 or 0x224 + V2
+    0x00, 0x00, // 0x220: never reached
+    0x00, 0x00, // 0x222: never reached
+    0x65, 0x02, // 0x224: V5 = 2
+    0x12, 0x2C, // 0x226: jump to the end
+    0x65, 0x01, // 0x228: V5 = 1
+    0x12, 0x2C, // 0x22A: jump to the end
+    0x12, 0x2C, // 0x22C: halt
+];
+
+/// An emulator configuration, and the registers the quirks ROM should leave behind with it.
+struct QuirkCase {
+    name: &'static str,
+    configure: fn(&mut Emu),
+    /// (register, value)
+    expected: &'static [(usize, u8)],
+}
+
+const CASES: &[QuirkCase] = &[
+    QuirkCase {
+        name: "default",
+        configure: |_| {},
+        expected: &[
+            (0xE, 0x55), // VF is left alone by 8XY1
+            (0xD, 0x00), // 8XY6 shifts VX in place
+            (0x4, 0xFF), // FX55 leaves I alone
+            (0x5, 0x01), // BNNN jumps to NNN + V0
+        ],
+    },
+    QuirkCase {
+        name: "shift copies VY",
+        configure: |emu| {
+            emu.set_quirks(Quirks {
+                shift: ShiftQuirk::CopyY,
+                ..Quirks::default()
+            });
+        },
+        expected: &[(0xD, 0x01)], // 8XY6 shifts V3, whose low bit is set
+    },
+    QuirkCase {
+        name: "load/store adds X",
+        configure: |emu| {
+            emu.set_quirks(Quirks {
+                memory: MemoryQuirk::IncrementByX,
+                ..Quirks::default()
+            });
+        },
+        expected: &[(0x4, 0xF0)], // FX55 leaves I on the byte V1 went to, so FX65 reads V1
+    },
+    QuirkCase {
+        name: "load/store adds X + 1",
+        configure: |emu| {
+            emu.set_quirks(Quirks {
+                memory: MemoryQuirk::IncrementByXPlus1,
+                ..Quirks::default()
+            });
+        },
+        expected: &[(0x4, 0x00)], // FX55 leaves I past V1, where nothing was stored
+    },
+    QuirkCase {
+        name: "jump adds VX",
+        configure: |emu| {
+            emu.set_quirks(Quirks {
+                jump: JumpQuirk::Vx,
+                ..Quirks::default()
+            });
+        },
+        expected: &[(0x5, 0x02)], // B224 jumps to 0x224 + V2
+    },
+    QuirkCase {
+        name: "logic resets VF",
+        configure: |emu| {
+            emu.set_quirks(Quirks {
+                logic_vf_reset: true,
+                ..Quirks::default()
+            });
+        },
+        expected: &[(0xE, 0x00)], // 8XY1 clears VF
+    },
+    QuirkCase {
+        name: "COSMAC VIP",
+        configure: |emu| {
+            emu.set_quirks(Quirks {
+                shift: ShiftQuirk::CopyY,
+                memory: MemoryQuirk::IncrementByXPlus1,
+                logic_vf_reset: true,
+                ..Quirks::default()
+            });
+        },
+        expected: &[(0xE, 0x00), (0xD, 0x01), (0x4, 0x00), (0x5, 0x01)],
+    },
+];
+
+#[test]
+fn test_quirks_rom() {
+    for case in CASES {
+        let mut emu = Emu::new();
+        (case.configure)(&mut emu);
+        emu.load_rom(&QUIRKS_ROM).unwrap();
+
+        // there is nothing to draw, so this runs until the ROM halts
+        emu.run_until_display().unwrap();
+
+        for &(register, value) in case.expected {
+            assert_eq!(
+                emu.registers()[register],
+                value,
+                "{}: V{register:X} should be {value:#04X}",
+                case.name
+            );
+        }
+    }
+}
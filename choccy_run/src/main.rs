@@ -0,0 +1,168 @@
+#![warn(missing_docs, missing_debug_implementations, rust_2018_idioms)]
+#![warn(clippy::pedantic)]
+
+//! Choccy Run runs a CHIP-8 ROM without a TUI, for scripts and CI.
+//! It prints the final screen as ASCII art and the registers.
+
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+
+use choccy_chip::prelude::*;
+use clap::Parser;
+use color_eyre::eyre::WrapErr;
+use color_eyre::Result;
+
+/// How many instructions run between two ticks of the timers, the TUI's normal speed.
+const INSTRUCTIONS_PER_FRAME: u64 = 10;
+
+/// Runs a CHIP-8 ROM headless, then prints the screen and registers.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// The ROM to run
+    rom: PathBuf,
+    /// The most instructions to run before stopping
+    #[arg(short, long, default_value_t = 10_000)]
+    cycles: u64,
+    /// Mirror or rotate the printed screen: none, flip-h, flip-v or rotate180
+    #[arg(long, default_value = "none")]
+    transform: ScreenTransform,
+}
+
+/// Why a run stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stop {
+    /// The program jumped to itself, see [`CycleOutcome::Halted`].
+    Halted,
+    /// The program is waiting for a key, which will never come.
+    WaitingForKey,
+    /// The cycle limit was reached.
+    OutOfCycles,
+}
+
+/// Runs the loaded program until it halts or waits for a key, or `max_cycles` instructions
+/// have run. The timers are ticked every [`INSTRUCTIONS_PER_FRAME`] instructions.
+/// Returns why the run stopped and how many instructions ran.
+fn run(emu: &mut Emu, max_cycles: u64) -> Result<(Stop, u64), EmuError> {
+    let mut cycles = 0;
+    while cycles < max_cycles {
+        match emu.cycle()? {
+            CycleOutcome::Executed => {}
+            CycleOutcome::WaitingForFrame => {
+                // nothing ran, the frame is over early
+                emu.tick_timers();
+                continue;
+            }
+            CycleOutcome::WaitingForKey => return Ok((Stop::WaitingForKey, cycles + 1)),
+            CycleOutcome::Halted => return Ok((Stop::Halted, cycles + 1)),
+        }
+        cycles += 1;
+        if cycles % INSTRUCTIONS_PER_FRAME == 0 {
+            emu.tick_timers();
+        }
+    }
+    Ok((Stop::OutOfCycles, cycles))
+}
+
+/// The screen as ASCII art after `transform`, one line per row, `#` for a lit pixel and `.`
+/// for an unlit one.
+fn screen_ascii(emu: &Emu, transform: ScreenTransform) -> String {
+    let rows: Vec<&[bool]> = emu.screen_rows().collect();
+    let mut ascii = String::new();
+    for y in 0..SCREEN_HEIGHT {
+        let line: String = (0..SCREEN_WIDTH)
+            .map(|x| {
+                // every transform is its own inverse, so this finds the pixel shown here
+                let (x, y) = transform.apply(x, y);
+                if rows[y][x] {
+                    '#'
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        let _ = writeln!(ascii, "{line}");
+    }
+    ascii
+}
+
+/// The registers V0 to VF and the program counter, in hex.
+fn register_dump(emu: &Emu) -> String {
+    let mut dump = String::new();
+    for (i, value) in emu.registers().iter().enumerate() {
+        let _ = write!(dump, "V{i:X}={value:02X} ");
+    }
+    let _ = write!(dump, "PC={:04X}", emu.program_counter());
+    dump
+}
+
+fn main() -> Result<()> {
+    color_eyre::install()?;
+    let cli = Cli::parse();
+    let rom = fs::read(&cli.rom).wrap_err_with(|| format!("failed to read {}", cli.rom.display()))?;
+
+    let mut emu = Emu::new();
+    emu.load_rom(&rom)?;
+    let (stop, cycles) = run(&mut emu, cli.cycles)?;
+
+    print!("{}", screen_ascii(&emu, cli.transform));
+    println!("{}", register_dump(&emu));
+    println!("{stop:?} after {cycles} cycles");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run() {
+        let mut emu = Emu::new();
+        // draw the 0 font sprite in the top left corner, then jump to itself
+        emu.load_rom(&[0x60, 0x00, 0xF0, 0x29, 0xD0, 0x05, 0x12, 0x06]).unwrap();
+
+        assert_eq!(run(&mut emu, 100), Ok((Stop::Halted, 4)));
+
+        let ascii = screen_ascii(&emu, ScreenTransform::None);
+        let lines: Vec<&str> = ascii.lines().collect();
+        assert_eq!(lines.len(), SCREEN_HEIGHT);
+        let art = ["####", "#..#", "#..#", "#..#", "####", "...."];
+        for (line, art) in lines.iter().zip(art) {
+            assert_eq!(line.len(), SCREEN_WIDTH);
+            assert!(line.starts_with(art));
+            assert!(!line[art.len()..].contains('#'));
+        }
+
+        assert_eq!(
+            register_dump(&emu),
+            "V0=00 V1=00 V2=00 V3=00 V4=00 V5=00 V6=00 V7=00 \
+             V8=00 V9=00 VA=00 VB=00 VC=00 VD=00 VE=00 VF=00 PC=0206"
+        );
+    }
+
+    #[test]
+    fn test_screen_ascii_transform() {
+        let mut emu = Emu::new();
+        // draw the 1 font sprite in the top left corner, then jump to itself
+        emu.load_rom(&[0x61, 0x01, 0xF1, 0x29, 0xD0, 0x05, 0x12, 0x06]).unwrap();
+        run(&mut emu, 100).unwrap();
+
+        let ascii = screen_ascii(&emu, ScreenTransform::FlipH);
+        let lines: Vec<&str> = ascii.lines().collect();
+        // the 1 is drawn from the right edge, mirrored
+        assert!(lines[0].ends_with("#.."));
+        assert!(lines[1].ends_with("##."));
+        assert!(!lines[0][..SCREEN_WIDTH - 3].contains('#'));
+    }
+
+    #[test]
+    fn test_run_out_of_cycles() {
+        let mut emu = Emu::new();
+        // add 1 to V0 forever
+        emu.load_rom(&[0x70, 0x01, 0x12, 0x00]).unwrap();
+
+        assert_eq!(run(&mut emu, 10), Ok((Stop::OutOfCycles, 10)));
+        assert_eq!(emu.registers()[0], 5);
+    }
+}
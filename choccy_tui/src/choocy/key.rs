@@ -1,65 +1,449 @@
+use std::time::Duration;
+
 use color_eyre::Result;
-use color_eyre::eyre::{bail, WrapErr};
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
-use super::{App, EmulateState};
+use color_eyre::eyre::WrapErr;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use super::{App, CurrentScreen, EmulateState, Speed, KEYPAD_LAYOUT, KEYPAD_WIDTH};
+
+/// Moves the remap selection on the [`KEYPAD_LAYOUT`] grid with the arrow keys, wrapping
+/// around at the edges. Any other key leaves it where it is.
+fn move_selection(selected: usize, code: KeyCode) -> usize {
+    let rows = KEYPAD_LAYOUT.len() / KEYPAD_WIDTH;
+    let (row, col) = (selected / KEYPAD_WIDTH, selected % KEYPAD_WIDTH);
+    let (row, col) = match code {
+        KeyCode::Up => ((row + rows - 1) % rows, col),
+        KeyCode::Down => ((row + 1) % rows, col),
+        KeyCode::Left => (row, (col + KEYPAD_WIDTH - 1) % KEYPAD_WIDTH),
+        KeyCode::Right => (row, (col + 1) % KEYPAD_WIDTH),
+        _ => (row, col),
+    };
+    row * KEYPAD_WIDTH + col
+}
+
+/// The speed preset bound to a key: shift and 1, 2 or 3 for slow, normal or fast.
+/// Terminals report shift+1 as either `!` or `1` with shift held, so both are accepted.
+/// The unshifted number keys are left for the CHIP-8 keypad.
+fn speed_for_key(key_event: KeyEvent) -> Option<Speed> {
+    let shift = key_event.modifiers.contains(KeyModifiers::SHIFT);
+    match key_event.code {
+        KeyCode::Char('!') => Some(Speed::Slow),
+        KeyCode::Char('@') => Some(Speed::Normal),
+        KeyCode::Char('#') => Some(Speed::Fast),
+        KeyCode::Char('1') if shift => Some(Speed::Slow),
+        KeyCode::Char('2') if shift => Some(Speed::Normal),
+        KeyCode::Char('3') if shift => Some(Speed::Fast),
+        _ => None,
+    }
+}
 
 impl App {
-    pub fn handle_event(&mut self) -> Result<()> {
-        match event::read()? {
-            // it's important to check that the event is a key press event as
-            // crossterm also emits key release and repeat events on Windows.
-            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => self
+    /// Reads every event that is already waiting, without blocking, so keys pressed
+    /// at the same time are all handled in the same frame.
+    pub fn drain_events() -> Result<Vec<Event>> {
+        let mut events = Vec::new();
+        while event::poll(Duration::ZERO)? {
+            events.push(event::read()?);
+        }
+        Ok(events)
+    }
+
+    /// Handles a batch of events, in the order they happened.
+    pub fn handle_events(&mut self, events: impl IntoIterator<Item = Event>) -> Result<()> {
+        for event in events {
+            self.handle_event(&event)?;
+        }
+        Ok(())
+    }
+
+    pub fn handle_event(&mut self, event: &Event) -> Result<()> {
+        match *event {
+            // crossterm only emits key release events when the terminal supports them
+            // (see `tui::init`), repeats are ignored as the key is already held down.
+            Event::Key(key_event) if key_event.kind != KeyEventKind::Repeat => self
                 .handle_key_event(key_event)
                 .wrap_err_with(|| format!("handling key event failed:\n {key_event:#?}")),
-            // _ => {emu.fetch(); emu.excute} // our library needs to tell us when we need an input
             _ => Ok(()),
         }
     }
-    
-    pub fn handle_key_event(&mut self, key_event:KeyEvent) -> Result<()> {
-        if let KeyCode::Char(c) = key_event.code {
-            let key_str = c.to_string();
-            if let Some(&chip8_key) = self.emu.get_key_mapping(&key_str) {
-                self.emu.press_key(chip8_key);
-            }
+
+    #[allow(clippy::unnecessary_wraps)]
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
+        match self.current_screen {
+            CurrentScreen::Home => self.handle_home_key_event(key_event),
+            CurrentScreen::Emulate => self.handle_emulate_key_event(key_event),
+            CurrentScreen::Rom => {}
         }
         Ok(())
     }
 
+    fn handle_home_key_event(&mut self, key_event: KeyEvent) {
+        if key_event.kind != KeyEventKind::Press {
+            return;
+        }
+        match key_event.code {
+            KeyCode::Char('q') => self.quit = true,
+            KeyCode::Char('r') if self.rom_loaded => {
+                self.current_screen = CurrentScreen::Emulate;
+                self.state = EmulateState::Running;
+            }
+            // the sample ROMs are numbered from 1
+            KeyCode::Char(c @ '1'..='9') => self.load_sample(c as usize - '1' as usize),
+            _ => {}
+        }
+    }
+
+    fn handle_emulate_key_event(&mut self, key_event: KeyEvent) {
+        let pressed = key_event.kind == KeyEventKind::Press;
+        let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        if let Some(speed) = speed_for_key(key_event) {
+            // handled here, so the key never reaches the keypad
+            if pressed && self.state == EmulateState::Running {
+                self.opts.speed = speed;
+                self.show_toast(&format!("{speed:?} speed"));
+                self.update_config(|config| config.speed = Some(speed));
+            }
+            return;
+        }
+        match (key_event.code, &self.state) {
+            // turbo lasts for as long as the key is held, if the terminal says when it is let go
+            (KeyCode::Tab, _) if self.key_releases => self.turbo = pressed,
+            (KeyCode::Tab, _) if pressed => self.turbo = !self.turbo,
+            (KeyCode::Char('r'), _) if pressed && ctrl => self.restart(),
+            (KeyCode::Char('s'), _) if pressed && ctrl => {
+                self.opts.scanlines = !self.opts.scanlines;
+                let scanlines = self.opts.scanlines;
+                let state = if scanlines { "on" } else { "off" };
+                self.show_toast(&format!("Scanlines {state}"));
+                self.update_config(|config| config.scanlines = Some(scanlines));
+            }
+            (KeyCode::Esc, EmulateState::Running) if pressed => self.state = EmulateState::Paused,
+            (KeyCode::Char('r'), EmulateState::Paused) if pressed => {
+                self.state = EmulateState::Running;
+            }
+            (KeyCode::Char('m'), EmulateState::Paused) if pressed => {
+                self.state = EmulateState::Remapping {
+                    selected: 0,
+                    picked: false,
+                };
+            }
+            (KeyCode::Esc, EmulateState::Remapping { .. }) if pressed => {
+                self.state = EmulateState::Paused;
+            }
+            // every letter and digit can be a keyboard input, so the reset is on backspace
+            (KeyCode::Backspace, EmulateState::Remapping { .. }) if pressed => self.reset_keys(),
+            // first the CHIP-8 key, picked with the arrows and enter or typed as a hex digit,
+            // then the keyboard input for it
+            (
+                KeyCode::Up | KeyCode::Down | KeyCode::Left | KeyCode::Right,
+                &EmulateState::Remapping {
+                    selected,
+                    picked: false,
+                },
+            ) if pressed => {
+                self.state = EmulateState::Remapping {
+                    selected: move_selection(selected, key_event.code),
+                    picked: false,
+                };
+            }
+            (KeyCode::Enter, &EmulateState::Remapping { selected, .. }) if pressed => {
+                self.state = EmulateState::Remapping {
+                    selected,
+                    picked: true,
+                };
+            }
+            (KeyCode::Char(c), EmulateState::Remapping { picked: false, .. }) if pressed => {
+                let typed = c.to_digit(16).and_then(|digit| {
+                    KEYPAD_LAYOUT.iter().position(|&key| key == digit as usize)
+                });
+                if let Some(selected) = typed {
+                    self.state = EmulateState::Remapping {
+                        selected,
+                        picked: true,
+                    };
+                }
+            }
+            (KeyCode::Char(c), &EmulateState::Remapping { selected, picked: true }) if pressed => {
+                self.remap_key(c, KEYPAD_LAYOUT[selected]);
+            }
+            // released in any state, so a key held through the pause menu is not stuck down
+            (KeyCode::Char(c), _) if !pressed => {
+                if let Some(&chip8_key) = self.emu.get_key_mapping(&c.to_string()) {
+                    self.emu.release_key(chip8_key);
+                }
+            }
+            (KeyCode::Char('q'), EmulateState::Paused | EmulateState::Error) if pressed => {
+                self.quit = true;
+            }
+            (KeyCode::Char(c), EmulateState::Running) => {
+                if let Some(&chip8_key) = self.emu.get_key_mapping(&c.to_string()) {
+                    self.press_key(chip8_key);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::choocy::{KEY_TAP_DURATION, TURBO_MULTIPLIER};
+    use std::time::Instant;
+    use choccy_chip::prelude::OpCode;
+    use crate::config::Config;
+    use crossterm::event::KeyEventState;
+
+    fn key(code: KeyCode, kind: KeyEventKind) -> KeyEvent {
+        KeyEvent {
+            code,
+            modifiers: KeyModifiers::NONE,
+            kind,
+            state: KeyEventState::NONE,
+        }
+    }
+
+    #[test]
+    fn test_reset_key() {
+        let mut app = App::new(Config::default(), Config::default());
+        app.load_rom(&[0x60, 0x01, 0x12, 0x02]).unwrap();
+        app.current_screen = CurrentScreen::Emulate;
+        app.state = EmulateState::Running;
+        app.emu.cycle().unwrap();
+        assert_ne!(app.emu.program_counter(), 0x200);
+
+        let mut ctrl_r = key(KeyCode::Char('r'), KeyEventKind::Press);
+        ctrl_r.modifiers = KeyModifiers::CONTROL;
+        app.handle_key_event(ctrl_r).unwrap();
+
+        assert_eq!(app.emu.program_counter(), 0x200);
+        assert_eq!(app.emu.peek_opcode(), Ok(OpCode::Constant((6, 0, 1))));
+        assert!(app.rom_loaded);
+        assert_eq!(app.toast(), Some("Reset"));
+    }
+
+    #[test]
+    fn test_handle_events_batch() {
+        let mut app = App::new(Config::default(), Config::default());
+        app.current_screen = CurrentScreen::Emulate;
+        app.state = EmulateState::Running;
+
+        // pressed in the same frame
+        let events = [
+            Event::Key(key(KeyCode::Tab, KeyEventKind::Press)),
+            Event::Key(key(KeyCode::Esc, KeyEventKind::Press)),
+        ];
+        app.handle_events(events).unwrap();
+
+        assert!(app.turbo);
+        assert_eq!(app.state, EmulateState::Paused);
+    }
+
+    #[test]
+    fn test_speed_for_key() {
+        let mut shift_1 = key(KeyCode::Char('1'), KeyEventKind::Press);
+        shift_1.modifiers = KeyModifiers::SHIFT;
+        assert_eq!(speed_for_key(shift_1), Some(Speed::Slow));
+        assert_eq!(
+            speed_for_key(key(KeyCode::Char('#'), KeyEventKind::Press)),
+            Some(Speed::Fast)
+        );
+
+        // the keypad keys are not speed presets
+        let app = App::new(Config::default(), Config::default());
+        for input in ["1", "2", "3", "4"] {
+            assert!(app.emu.get_key_mapping(input).is_some());
+            let c = input.chars().next().unwrap();
+            assert_eq!(speed_for_key(key(KeyCode::Char(c), KeyEventKind::Press)), None);
+        }
+    }
+
+    #[test]
+    fn test_speed_key() {
+        let mut app = App::new(Config::default(), Config::default());
+        app.current_screen = CurrentScreen::Emulate;
+        app.state = EmulateState::Running;
+
+        app.handle_key_event(key(KeyCode::Char('#'), KeyEventKind::Press)).unwrap();
+        assert_eq!(app.opts.speed, Speed::Fast);
+        assert_eq!(app.toast(), Some("Fast speed"));
+        assert!(app.emu.keys_pressed().is_empty());
+    }
+
+    #[test]
+    fn test_settings_saved() {
+        let path = std::env::temp_dir()
+            .join(format!("choccy-settings-test-{}", std::process::id()))
+            .join("config.toml");
+        let mut app = App::new(Config::default(), Config::default()).saving_to(Some(path.clone()));
+        app.current_screen = CurrentScreen::Emulate;
+        app.state = EmulateState::Running;
+
+        app.handle_key_event(key(KeyCode::Char('!'), KeyEventKind::Press)).unwrap();
+        let mut ctrl_s = key(KeyCode::Char('s'), KeyEventKind::Press);
+        ctrl_s.modifiers = KeyModifiers::CONTROL;
+        app.handle_key_event(ctrl_s).unwrap();
+        app.remap_key('t', 0xA);
+        app.remap_key('y', 0xA);
+
+        let saved = Config::load_from(&path).unwrap();
+        assert_eq!(saved.speed, Some(Speed::Slow));
+        assert_eq!(saved.scanlines, Some(true));
+        // the old input for the key is forgotten, as in the emulator
+        assert_eq!(
+            saved.keymap,
+            Some(std::collections::HashMap::from([("y".to_string(), 0xA)]))
+        );
+
+        app.state = EmulateState::Paused;
+        for code in [KeyCode::Char('m'), KeyCode::Backspace] {
+            app.handle_key_event(key(code, KeyEventKind::Press)).unwrap();
+        }
+        let saved = Config::load_from(&path).unwrap();
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert_eq!(saved.keymap, None);
+    }
+
+    #[test]
+    fn test_turbo_key() {
+        let mut app = App::new(Config::default(), Config::default());
+        app.current_screen = CurrentScreen::Emulate;
+        app.state = EmulateState::Running;
+        app.key_releases = true;
+        let normal = app.instructions_per_frame();
+
+        app.handle_key_event(key(KeyCode::Tab, KeyEventKind::Press)).unwrap();
+        assert!(app.turbo);
+        assert_eq!(app.instructions_per_frame(), normal * TURBO_MULTIPLIER);
+
+        app.handle_key_event(key(KeyCode::Tab, KeyEventKind::Release)).unwrap();
+        assert!(!app.turbo);
+        assert_eq!(app.instructions_per_frame(), normal);
+    }
+
+    #[test]
+    fn test_without_key_releases() {
+        let mut app = App::new(Config::default(), Config::default());
+        app.current_screen = CurrentScreen::Emulate;
+        app.state = EmulateState::Running;
+        assert!(!app.key_releases);
 
+        // tab toggles turbo, as it is never released
+        app.handle_key_event(key(KeyCode::Tab, KeyEventKind::Press)).unwrap();
+        assert!(app.turbo);
+        app.handle_key_event(key(KeyCode::Tab, KeyEventKind::Press)).unwrap();
+        assert!(!app.turbo);
 
-    // fn correct_map(remap: self.HashMap<"og key", "user remaped key">, key) -> Key {
-    //     return ramap.get(key);
-    // }
-    //
-    // fn handle_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
-    //     // TODO; figure how we are going to handle custom inputs
-    //     let key = correct_map(key_event.code);
-    //     handle_key(key)
-    // }
-    //
-    // fn handle_key(key: KeyCode) {
-    //     match self.state {
-    //         EmulateState::Running => {
-    //             // emu.handle_key(key)
-    //         }
-    //         EmulateState::Paused => {
-    //             match key {
-    //                 KeyCode::Char('r') => self.handle_r(),
-    //                 _ => Ok(()),
-    //             }
-    //         }
-    //         EmulateState::Off => {
-    //             match key {
-    //                 KeyCode::Char('q') => self.handle_q(),
-    //                 KeyCode::Char('r') => self.handle_r(),
-    //                 _ => Ok(()),
-    //             }
-    //         }
-    //         EmulateState::Error => {
-    //             bail!("Error state, cannot handle key");
-    //         }
-    //     }
-    // }
+        // keypad keys are let go a moment after they were pressed
+        let pressed = Instant::now();
+        app.handle_key_event(key(KeyCode::Char('x'), KeyEventKind::Press)).unwrap();
+        app.release_tapped_keys(pressed);
+        assert_eq!(app.emu.keys_pressed(), vec![0x0]);
+        app.release_tapped_keys(Instant::now() + KEY_TAP_DURATION);
+        assert!(app.emu.keys_pressed().is_empty());
+    }
+
+    #[test]
+    fn test_release_while_paused() {
+        let mut app = App::new(Config::default(), Config::default());
+        app.current_screen = CurrentScreen::Emulate;
+        app.state = EmulateState::Running;
+        app.key_releases = true;
+
+        app.handle_key_event(key(KeyCode::Char('x'), KeyEventKind::Press)).unwrap();
+        app.handle_key_event(key(KeyCode::Esc, KeyEventKind::Press)).unwrap();
+        app.handle_key_event(key(KeyCode::Char('x'), KeyEventKind::Release)).unwrap();
+
+        assert_eq!(app.state, EmulateState::Paused);
+        assert!(app.emu.keys_pressed().is_empty());
+    }
+
+    #[test]
+    fn test_reset_keys() {
+        let mut app = App::new(Config::default(), Config::default());
+        app.current_screen = CurrentScreen::Emulate;
+        app.state = EmulateState::Paused;
+        app.emu.set_key_mapping("t", 0x0);
+
+        // from the pause menu into the remap overlay, then reset
+        for code in [KeyCode::Char('m'), KeyCode::Backspace] {
+            app.handle_key_event(key(code, KeyEventKind::Press)).unwrap();
+        }
+        assert_eq!(app.emu.get_key_mapping("t"), None);
+        assert_eq!(app.emu.get_key_mapping("x"), Some(&0x0));
+        assert_eq!(app.state, EmulateState::Running);
+        assert_eq!(app.toast(), Some("Default keys restored"));
+    }
+
+    #[test]
+    fn test_remap_overlay() {
+        let mut app = App::new(Config::default(), Config::default());
+        app.current_screen = CurrentScreen::Emulate;
+        app.state = EmulateState::Paused;
+        let press = |app: &mut App, c| {
+            app.handle_key_event(key(KeyCode::Char(c), KeyEventKind::Press)).unwrap();
+        };
+
+        press(&mut app, 'm');
+        let picking = |selected| EmulateState::Remapping {
+            selected,
+            picked: false,
+        };
+        assert_eq!(app.state, picking(0));
+        press(&mut app, 'g'); // not a CHIP-8 key
+        assert_eq!(app.state, picking(0));
+        press(&mut app, 'a');
+        assert_eq!(
+            app.state,
+            EmulateState::Remapping {
+                selected: 12,
+                picked: true
+            }
+        );
+        press(&mut app, 't');
+
+        assert_eq!(app.state, EmulateState::Running);
+        assert_eq!(app.emu.get_key_mapping("t"), Some(&0xA));
+        assert_eq!(app.emu.get_key_mapping("z"), None);
+        assert_eq!(app.toast(), Some("t is now key A"));
+
+        // cancelling goes back to the pause menu
+        app.state = EmulateState::Paused;
+        press(&mut app, 'm');
+        app.handle_key_event(key(KeyCode::Esc, KeyEventKind::Press)).unwrap();
+        assert_eq!(app.state, EmulateState::Paused);
+    }
+
+    #[test]
+    fn test_move_selection() {
+        assert_eq!(move_selection(0, KeyCode::Right), 1);
+        assert_eq!(move_selection(0, KeyCode::Down), 4);
+        // wraps around at every edge of the 4x4 grid
+        assert_eq!(move_selection(0, KeyCode::Left), 3);
+        assert_eq!(move_selection(0, KeyCode::Up), 12);
+        assert_eq!(move_selection(3, KeyCode::Right), 0);
+        assert_eq!(move_selection(15, KeyCode::Down), 3);
+        assert_eq!(move_selection(5, KeyCode::Enter), 5);
+    }
 
+    #[test]
+    fn test_remap_overlay_arrows() {
+        let mut app = App::new(Config::default(), Config::default());
+        app.current_screen = CurrentScreen::Emulate;
+        app.state = EmulateState::Paused;
+
+        for code in [KeyCode::Char('m'), KeyCode::Up, KeyCode::Right, KeyCode::Enter] {
+            app.handle_key_event(key(code, KeyEventKind::Press)).unwrap();
+        }
+        // the bottom row, second from the left
+        assert_eq!(
+            app.state,
+            EmulateState::Remapping {
+                selected: 13,
+                picked: true
+            }
+        );
+
+        app.handle_key_event(key(KeyCode::Char('t'), KeyEventKind::Press)).unwrap();
+        assert_eq!(app.emu.get_key_mapping("t"), Some(&0x0));
+    }
 }
@@ -1,65 +1,390 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use super::samples::SAMPLE_ROMS;
 use super::{ui::ui, App};
-use super::{CurrentScreen, EmulateOpts, EmulateState};
+use super::{CurrentScreen, EmulateOpts, EmulateState, KEY_TAP_DURATION, TOAST_DURATION};
+use super::TURBO_MULTIPLIER;
+use crate::config::Config;
 use crate::tui;
 use choccy_chip::emulator::emulator::Emu;
+use choccy_chip::emulator::NUM_KEYS;
 use color_eyre::eyre::WrapErr;
 use color_eyre::Result;
+use crossterm::event::{self, Event};
+
+/// How long a single frame lasts, the CHIP-8 runs at 60 frames a second.
+const FRAME_DURATION: Duration = Duration::from_micros(16_667);
 
 impl App {
     /// Handle key events
     pub fn run(&mut self, terminal: &mut tui::Tui) -> Result<()> {
-        // step 1. init the emulator
-        //  - init the screen / (I don't mean render)
-
+        self.key_releases = tui::key_releases_reported();
         while !self.quit {
-            // step 2. we render the screen
-            // - we need to render the home screen, not the emulator
-            terminal.draw(|f| ui(f, self))?; // Charlie
-
-            // step 3. handle key inputs
-            // - case work:
-            //  - 0. home screen (Albert)
-            //  - 1. remaping is entered by some key (Albert)
-            //  - 2. rom loaded (After albert is done, Danny)
-            //  - 3. emulator running (any)
-            match self.current_screen {
-                // <c-q> to quit  or <blackslash>
-                CurrentScreen::Remap => {
-                    // 1.remap
-                    todo!()
-                    // self.handle_remap().wrap_err("Failed to handle remap")?;
+            let frame_start = Instant::now();
+
+            // step 1. we render the screen
+            terminal.draw(|f| ui(f, self))?;
+
+            // step 2. handle key inputs until the frame is over
+            self.handle_frame_input(frame_start, |timeout| {
+                if event::poll(timeout)? {
+                    Self::drain_events().map(Some)
+                } else {
+                    Ok(None)
                 }
-                CurrentScreen::Home => self.handle_event().wrap_err("Failed to handle event")?, // 0. home screen
-                _ => todo!(), // CurrentScreen::Emulate => self.handle_emulate().wrap_err("Failed to handle emulate")?, // 3. emulator running
-                              // CurrentScreen::Rom
+            })?;
+
+            // step 3. emulate i.e., fetch and execute
+            if self.state == EmulateState::Running {
+                self.run_frame();
+                if self.ring_bell() && self.opts.bell {
+                    tui::bell()?;
+                }
+            }
+            self.release_tapped_keys(Instant::now());
+        }
+        Ok(())
+    }
+
+    /// Handles input until the frame that started at `frame_start` is over.
+    /// `next_events` waits up to the given timeout for events, returning `None` if there were none.
+    /// Input is always polled at least once, even if running the last frame took so long
+    /// that this one is already over, so a busy ROM cannot make the app unresponsive.
+    pub(crate) fn handle_frame_input(
+        &mut self,
+        frame_start: Instant,
+        mut next_events: impl FnMut(Duration) -> Result<Option<Vec<Event>>>,
+    ) -> Result<()> {
+        loop {
+            let timeout = FRAME_DURATION.saturating_sub(frame_start.elapsed());
+            let Some(events) = next_events(timeout)? else {
+                return Ok(());
+            };
+            self.handle_events(events).wrap_err("Failed to handle event")?;
+            if timeout.is_zero() {
+                return Ok(());
             }
+        }
+    }
+
+    /// Runs one frame worth of instructions, then ticks the timers.
+    /// The frame ends early if the emulator is waiting for the next one or has halted,
+    /// see [`Emu::tick_frame`].
+    pub(crate) fn run_frame(&mut self) {
+        if let Err(err) = self.emu.tick_frame(self.instructions_per_frame()) {
+            self.error = Some(err.to_string());
+            self.state = EmulateState::Error;
+        }
+    }
+
+    /// Presses the keypad `key`. Without key releases, it is released again once it has not been
+    /// pressed for [`KEY_TAP_DURATION`], see [`App::release_tapped_keys`].
+    pub(crate) fn press_key(&mut self, key: usize) {
+        self.emu.press_key(key);
+        if !self.key_releases {
+            self.tapped.retain(|&(tapped, _)| tapped != key);
+            self.tapped.push((key, Instant::now()));
+        }
+    }
 
-            let condition: bool = true;
-
-            // step 4. emulate i.e., fetch and execute
-            if self.state == EmulateState::Running && condition {
-                // charlie is handling, emu error and cycle
-                // self.emu.cycle().wrap_err("Failed to cycle")?;
-                //
-                // albert
-                // audio
-                // call tick timer, a bool for audio
-                // if true, play audio
+    /// Releases the keys pressed more than [`KEY_TAP_DURATION`] before `now`, as the terminal
+    /// will never say they were let go.
+    pub(crate) fn release_tapped_keys(&mut self, now: Instant) {
+        let emu = &mut self.emu;
+        self.tapped.retain(|&(key, pressed)| {
+            let held = now.duration_since(pressed) < KEY_TAP_DURATION;
+            if !held {
+                emu.release_key(key);
             }
+            held
+        });
+    }
+
+    /// Whether the emulator started beeping since the last call.
+    /// Only the start of a beep is reported, so it is signalled once however long it lasts.
+    fn beep_started(&mut self) -> bool {
+        let was_beeping = std::mem::replace(&mut self.beeping, self.emu.is_beeping());
+        self.beeping && !was_beeping
+    }
 
-            // at this point, if the emulator is running, we made a cycle
-            // if not, we handled everything
+    /// Whether to ring the bell for this frame: a beep started, see [`App::beep_started`], and
+    /// at least [`EmulateOpts::beep_cooldown`](super::EmulateOpts) frames have passed since it
+    /// last rang, so a game that keeps restarting short beeps does not ring it over and over.
+    fn ring_bell(&mut self) -> bool {
+        if !self.beep_started() {
+            return false;
         }
+        let frame = self.emu.frame();
+        // the frame count starts again from zero when the emulator is reset
+        let cooled_down = match self.last_bell.and_then(|last| frame.checked_sub(last)) {
+            Some(frames_since) => frames_since >= self.opts.beep_cooldown,
+            None => true,
+        };
+        if cooled_down {
+            self.last_bell = Some(frame);
+        }
+        cooled_down
+    }
+
+    /// How many instructions to run this frame, taking the turbo key into account,
+    /// capped at the instruction budget.
+    pub fn instructions_per_frame(&self) -> usize {
+        let instructions = self.opts.speed.instructions_per_frame();
+        let instructions = if self.turbo {
+            instructions * TURBO_MULTIPLIER
+        } else {
+            instructions
+        };
+        instructions.min(self.opts.budget)
+    }
+
+    /// Loads a ROM into the emulator, ready to be run from the home screen,
+    /// with a toast saying which interpreter it was written for.
+    pub fn load_rom(&mut self, rom: &[u8]) -> Result<()> {
+        let variant = self.emu.load_rom_detecting_variant(rom)?;
+        self.rom_loaded = true;
+        self.show_toast(&format!("Loaded {variant} ROM"));
         Ok(())
     }
 
-    pub fn new() -> Self {
+    /// Loads one of the built-in [`SAMPLE_ROMS`] and starts running it.
+    /// Does nothing if there is no sample at `index`.
+    pub fn load_sample(&mut self, index: usize) {
+        let Some(sample) = SAMPLE_ROMS.get(index) else {
+            return;
+        };
+        self.emu.load_validated(&sample.validate(), false);
+        self.rom_loaded = true;
+        self.current_screen = CurrentScreen::Emulate;
+        self.state = EmulateState::Running;
+    }
+
+    /// Restarts the loaded ROM from the beginning, like pressing reset on a console.
+    pub fn restart(&mut self) {
+        self.emu.reset_preserving_rom();
+        self.error = None;
+        self.state = EmulateState::Running;
+        self.show_toast("Reset");
+    }
+
+    /// Maps the keyboard input `input` to the CHIP-8 `key`, as picked in the remap overlay,
+    /// then resumes the game.
+    pub fn remap_key(&mut self, input: char, key: usize) {
+        self.emu.set_key_mapping(&input.to_string(), key);
+        self.state = EmulateState::Running;
+        self.show_toast(&format!("{input} is now key {key:X}"));
+        self.update_config(|config| {
+            let keymap = config.keymap.get_or_insert_with(HashMap::new);
+            // like the emulator, unbind whatever input the key had before
+            keymap.retain(|_, &mut mapped| mapped != key);
+            keymap.insert(input.to_string(), key);
+        });
+    }
+
+    /// Restores the default keyboard layout, as asked for in the remap overlay,
+    /// then resumes the game.
+    pub fn reset_keys(&mut self) {
+        self.emu.reset_key_mapping();
+        self.state = EmulateState::Running;
+        self.show_toast("Default keys restored");
+        self.update_config(|config| config.keymap = None);
+    }
+
+    /// Shows a short message in the footer for [`TOAST_DURATION`].
+    pub fn show_toast(&mut self, message: &str) {
+        self.toast = Some((message.to_string(), Instant::now()));
+    }
+
+    /// The toast message, if one was shown recently.
+    pub fn toast(&self) -> Option<&str> {
+        self.toast
+            .as_ref()
+            .filter(|(_, shown)| shown.elapsed() < TOAST_DURATION)
+            .map(|(message, _)| message.as_str())
+    }
+
+    /// Creates the app from the saved `config`, with the command line `overrides` layered on top.
+    pub fn new(config: Config, overrides: Config) -> Self {
+        let settings = Config::defaults().merge(config.clone()).merge(overrides);
+
+        let mut emu = Emu::new();
+        if let Some(quirks) = settings.quirks {
+            emu.set_quirks(quirks);
+        }
+        for (input, &key) in settings.keymap.iter().flatten() {
+            if key < NUM_KEYS {
+                emu.set_key_mapping(input, key);
+            }
+        }
+
         Self {
-            emu: Emu::new(),
+            emu,
             current_screen: CurrentScreen::Home,
             state: EmulateState::Off,
-            opts: EmulateOpts::default(),
+            opts: EmulateOpts::from(&settings),
+            config,
+            config_path: None,
+            rom_loaded: false,
+            turbo: false,
+            key_releases: false, // found out when the terminal is set up in `run`
+            tapped: Vec::new(),
+            error: None,
+            toast: None,
+            beeping: false,
+            last_bell: None,
             quit: false,
         }
     }
+
+    /// Saves any preferences changed while running to the config file at `path`, see
+    /// [`App::update_config`]. Without a path, as in the tests, they only last for this run.
+    #[must_use]
+    pub fn saving_to(mut self, path: Option<PathBuf>) -> Self {
+        self.config_path = path;
+        self
+    }
+
+    /// Applies a change to the saved preferences and writes them to the config file.
+    /// Failing to save is shown as a toast rather than stopping the game.
+    pub fn update_config(&mut self, change: impl FnOnce(&mut Config)) {
+        change(&mut self.config);
+        let Some(path) = &self.config_path else {
+            return;
+        };
+        if let Err(err) = self.config.save_to(path) {
+            self.show_toast(&format!("Failed to save settings: {err}"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::choocy::Speed;
+    use choccy_chip::prelude::{OpCode, Quirks};
+
+    #[test]
+    fn test_run_frame_display_wait() {
+        // draw, then loop back to draw again
+        let rom = [0xD0, 0x01, 0x12, 0x00];
+
+        let mut app = App::new(Config::default(), Config::default());
+        app.load_rom(&rom).unwrap();
+        app.run_frame();
+        // an even number of instructions ends back at the draw
+        assert_eq!(app.emu.program_counter(), 0x200);
+
+        let mut app = App::new(Config::default(), Config::default());
+        app.emu.set_quirks(Quirks {
+            display_wait: true,
+            ..Quirks::default()
+        });
+        app.load_rom(&rom).unwrap();
+
+        // only the draw runs, then the frame ends rather than spinning on the wait
+        app.run_frame();
+        assert_eq!(app.emu.program_counter(), 0x202);
+        // the next frame runs the jump and the next draw
+        app.run_frame();
+        assert_eq!(app.emu.program_counter(), 0x202);
+        assert_eq!(app.error, None);
+    }
+
+    #[test]
+    fn test_input_polled_every_frame() {
+        let mut app = App::new(Config::default(), Config::default());
+        // jump to itself
+        app.load_rom(&[0x12, 0x00]).unwrap();
+        app.state = EmulateState::Running;
+
+        for _ in 0..3 {
+            // the frame is already over, as if the last one ran long
+            let frame_start = Instant::now().checked_sub(FRAME_DURATION * 2).unwrap();
+            let mut polls = 0;
+            app.handle_frame_input(frame_start, |_| {
+                polls += 1;
+                Ok(None)
+            })
+            .unwrap();
+            assert_eq!(polls, 1);
+            app.run_frame();
+        }
+        assert_eq!(app.emu.frame(), 3);
+    }
+
+    #[test]
+    fn test_instruction_budget() {
+        let overrides = Config {
+            speed: Some(Speed::Fast),
+            budget: Some(50),
+            ..Config::default()
+        };
+        let mut app = App::new(Config::default(), overrides);
+        assert_eq!(app.instructions_per_frame(), 20);
+        app.turbo = true;
+        assert_eq!(app.instructions_per_frame(), 50);
+    }
+
+    #[test]
+    fn test_load_rom_toast() {
+        let mut app = App::new(Config::default(), Config::default());
+        // switch to high resolution, then jump to itself
+        app.load_rom(&[0x00, 0xFF, 0x12, 0x02]).unwrap();
+        assert_eq!(app.toast(), Some("Loaded SUPER-CHIP ROM"));
+    }
+
+    #[test]
+    fn test_load_sample() {
+        let mut app = App::new(Config::default(), Config::default());
+        app.load_sample(0);
+        assert!(app.rom_loaded);
+        assert_eq!(app.state, EmulateState::Running);
+        assert_eq!(app.emu.peek_opcode(), Ok(OpCode::Constant((6, 0, 0x0E))));
+
+        let mut app = App::new(Config::default(), Config::default());
+        app.load_sample(SAMPLE_ROMS.len());
+        assert!(!app.rom_loaded);
+    }
+
+    #[test]
+    fn test_beep_started() {
+        let mut app = App::new(Config::default(), Config::default());
+        // beep for 3 frames, then jump to itself
+        app.load_rom(&[0x60, 0x03, 0xF0, 0x18, 0x12, 0x04]).unwrap();
+
+        app.run_frame();
+        assert!(app.beep_started());
+        for _ in 0..5 {
+            app.run_frame();
+            assert!(!app.beep_started());
+        }
+
+        app.restart();
+        app.run_frame();
+        assert!(app.beep_started());
+    }
+
+    #[test]
+    fn test_bell_cooldown() {
+        let mut app = App::new(Config::default(), Config::default());
+        // jump to itself, the beeps are started from the test
+        app.load_rom(&[0x12, 0x00]).unwrap();
+
+        let mut rings = 0;
+        for frame in 0..12 {
+            if frame % 2 == 0 {
+                // a short beep, over by the end of the next frame
+                app.emu.execute_raw(0x6002).unwrap();
+                app.emu.execute_raw(0xF018).unwrap();
+            }
+            app.run_frame();
+            if app.ring_bell() {
+                rings += 1;
+            }
+        }
+        // 6 beeps started, but the bell only rang on frames 1 and 7
+        assert_eq!(rings, 2);
+    }
 }
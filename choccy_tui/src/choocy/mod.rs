@@ -4,16 +4,69 @@ mod logic;
 mod ui;
 /// Handles key events for the choocy TUI.
 mod key;
+/// ROMs built into the choocy TUI, to try it out without a ROM file.
+mod samples;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
 use choccy_chip::prelude::*;
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+
+/// How many terminal cells wide a CHIP-8 pixel is drawn by default.
+pub const SCALE_FACTOR: u16 = 1;
+
+/// How many times faster the emulator runs while the turbo key is held.
+pub const TURBO_MULTIPLIER: usize = 10;
+
+/// The most instructions run in a single frame by default, so a fast speed with turbo
+/// cannot starve input handling.
+pub const INSTRUCTION_BUDGET: usize = 1000;
+
+/// The fewest frames between two rings of the bell by default, so a game that keeps starting
+/// short beeps does not ring it every other frame.
+pub const BEEP_COOLDOWN: u64 = 6;
+
+/// The CHIP-8 keypad as laid out on the original hardware, row by row.
+pub const KEYPAD_LAYOUT: [usize; 16] = [
+    0x1, 0x2, 0x3, 0xC, //
+    0x4, 0x5, 0x6, 0xD, //
+    0x7, 0x8, 0x9, 0xE, //
+    0xA, 0x0, 0xB, 0xF, //
+];
+
+/// How many keys are in each row of [`KEYPAD_LAYOUT`].
+pub const KEYPAD_WIDTH: usize = 4;
+
+/// How long a keypad key stays held after it is pressed, on terminals that never report key
+/// releases. The terminal's key repeat keeps pressing it again while it is held down.
+pub const KEY_TAP_DURATION: Duration = Duration::from_millis(150);
+
+/// How long a toast message stays in the footer.
+pub const TOAST_DURATION: Duration = Duration::from_secs(2);
 
 
 #[derive(Debug)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct App {
     emu: Emu, // the actual emulator
     pub(crate) current_screen: CurrentScreen, // the current screen the user is looking at, and will later determine what is rendered.
     pub(crate) state: EmulateState,
     opts: EmulateOpts,
+    config: Config, // the preferences saved on disk, without any command line overrides
+    config_path: Option<PathBuf>, // where changed preferences are saved, if anywhere
     // current_rom : Option<Rom>,
+    rom_loaded: bool,
+    turbo: bool, // true while the turbo key is held down, or toggled on without key releases
+    key_releases: bool, // whether the terminal reports key releases, see `tui::init`
+    tapped: Vec<(usize, Instant)>, // keypad keys pressed without key releases, and when
+    error: Option<String>, // what went wrong when state is Error
+    toast: Option<(String, Instant)>, // a short message and when it was shown
+    beeping: bool, // whether the emulator was beeping at the end of the last frame
+    last_bell: Option<u64>, // the frame the bell last rang on
     quit: bool,
 }
 
@@ -23,25 +76,141 @@ pub enum EmulateState {
     Off, // home
     Running, // running
     Paused, // (paused)
+    /// remapping a key without leaving the game: the highlighted cell of [`KEYPAD_LAYOUT`],
+    /// and whether its CHIP-8 key has been picked and is waiting for a keyboard key
+    Remapping { selected: usize, picked: bool },
     Error,
 }
 
-#[derive(Debug, Default)]
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug)]
 pub struct EmulateOpts {
-    pub sound: bool,
+    pub bell: bool, // the terminal bell stands in for sound, as there is no audio yet
+    pub beep_cooldown: u64, // in frames
     pub debug: bool,
+    pub scanlines: bool, // purely cosmetic, the emulator never sees it
+    pub half_blocks: bool, // draw the screen as text instead of on a canvas
+    pub transform: ScreenTransform,
+    pub theme: Theme,
     // pub remap: HashMap<KeyCode, KeyCode>,
-    // pub speed: u8,
+    pub speed: Speed,
+    pub scale: u16, // how many cells wide a pixel is
+    pub scale_y: u16, // how many half cells tall a pixel is
+    pub budget: usize, // the most instructions run in a frame
     // pub rom: Option<Rom>,
 }
 
+impl Default for EmulateOpts {
+    fn default() -> Self {
+        Self::from(&Config::defaults())
+    }
+}
+
+impl From<&Config> for EmulateOpts {
+    fn from(config: &Config) -> Self {
+        Self {
+            bell: config.bell.unwrap_or(true),
+            beep_cooldown: config.beep_cooldown.unwrap_or(BEEP_COOLDOWN),
+            debug: config.debug.unwrap_or_default(),
+            scanlines: config.scanlines.unwrap_or_default(),
+            half_blocks: config.half_blocks.unwrap_or_default(),
+            transform: config.transform.unwrap_or_default(),
+            theme: config.theme.unwrap_or_default(),
+            speed: config.speed.unwrap_or_default(),
+            scale: config.scale.unwrap_or(SCALE_FACTOR),
+            // pixels stay square unless a height is given
+            scale_y: config.scale_y.or(config.scale).unwrap_or(SCALE_FACTOR),
+            budget: config.budget.unwrap_or(INSTRUCTION_BUDGET),
+        }
+    }
+}
+
+/// How fast the emulator runs.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Speed {
+    Slow,
+    #[default]
+    Normal,
+    Fast,
+}
+
+impl FromStr for Speed {
+    type Err = String;
+
+    /// Parses `slow`, `normal` or `fast`, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "slow" => Ok(Speed::Slow),
+            "normal" => Ok(Speed::Normal),
+            "fast" => Ok(Speed::Fast),
+            _ => Err(format!("unknown speed '{s}', expected slow, normal or fast")),
+        }
+    }
+}
+
+impl Speed {
+    /// How many instructions are executed every frame (60 frames a second).
+    pub fn instructions_per_frame(self) -> usize {
+        match self {
+            Speed::Slow => 5,
+            Speed::Normal => 10,
+            Speed::Fast => 20,
+        }
+    }
+}
+
+/// The colors the screen is drawn in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    #[default]
+    White,
+    Green, // like a phosphor monitor
+    Amber,
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    /// Parses `white`, `green` or `amber`, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "white" => Ok(Theme::White),
+            "green" => Ok(Theme::Green),
+            "amber" => Ok(Theme::Amber),
+            _ => Err(format!("unknown theme '{s}', expected white, green or amber")),
+        }
+    }
+}
+
+impl Theme {
+    /// The color of a lit pixel.
+    pub fn pixel_color(self) -> Color {
+        match self {
+            Theme::White => Color::White,
+            Theme::Green => Color::LightGreen,
+            Theme::Amber => Color::LightYellow,
+        }
+    }
+
+    /// The color of a lit pixel on a dimmed row, when the scanline effect is on.
+    pub fn scanline_color(self) -> Color {
+        match self {
+            Theme::White => Color::Gray,
+            Theme::Green => Color::Green,
+            Theme::Amber => Color::Yellow,
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy)]
 pub enum CurrentScreen {
     #[default]
     Home, // press r to start, q to quit
+    #[allow(dead_code)]
     Rom,  // maybe we want this, to load a roam from a path. Not sure
     Emulate, // Emulate the device
-    Remap,
 }
 
 // danny needs to do rom,
@@ -49,3 +218,23 @@ pub enum CurrentScreen {
 // - you need to 1. use something like clap to parse the args
 // - and also, write the logic to load the rom
 // document what the hrz is because you looked through other emulators
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_speed() {
+        assert_eq!("slow".parse(), Ok(Speed::Slow));
+        assert_eq!("Normal".parse(), Ok(Speed::Normal));
+        assert_eq!("FAST".parse(), Ok(Speed::Fast));
+        assert!("warp".parse::<Speed>().is_err());
+    }
+
+    #[test]
+    fn test_parse_theme() {
+        assert_eq!("green".parse(), Ok(Theme::Green));
+        assert_eq!("AMBER".parse(), Ok(Theme::Amber));
+        assert!("purple".parse::<Theme>().is_err());
+    }
+}
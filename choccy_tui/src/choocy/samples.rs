@@ -0,0 +1,45 @@
+use choccy_chip::emulator::rom_parser::ValidRom;
+
+/// A small ROM built into the app, so there is something to run without a ROM file.
+#[derive(Debug, Clone, Copy)]
+pub struct SampleRom {
+    pub name: &'static str,
+    pub bytes: &'static [u8],
+}
+
+/// The ROMs offered on the home screen, picked with the number keys starting at 1.
+/// Both were written for choccy, see `roms/` for what they do.
+pub const SAMPLE_ROMS: [SampleRom; 2] = [
+    SampleRom {
+        name: "Splash",
+        bytes: include_bytes!("../../roms/splash.ch8"),
+    },
+    SampleRom {
+        name: "Keypad test",
+        bytes: include_bytes!("../../roms/keypad.ch8"),
+    },
+];
+
+impl SampleRom {
+    /// The ROM checked to fit in memory at the default start address.
+    pub fn validate(&self) -> ValidRom {
+        ValidRom::try_from(self.bytes).expect("sample ROMs fit in memory")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use choccy_chip::prelude::*;
+
+    #[test]
+    fn test_sample_roms_load() {
+        for sample in SAMPLE_ROMS {
+            let mut emu = Emu::new();
+            emu.load_validated(&sample.validate(), false);
+            assert!(emu.take_warnings().is_empty(), "{}", sample.name);
+            // runs until the first draw, or waits for a key
+            emu.run_until_display().unwrap();
+        }
+    }
+}
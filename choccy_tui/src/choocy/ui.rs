@@ -1,7 +1,14 @@
-use super::{App, CurrentScreen, EmulateState};
+use std::ops::Range;
+
+use super::samples::SAMPLE_ROMS;
+use super::{App, CurrentScreen, EmulateState, Theme, KEYPAD_LAYOUT, KEYPAD_WIDTH};
+use choccy_chip::emulator::RAM_SIZE;
+use choccy_chip::prelude::*;
 use ratatui::layout::{Constraint, Direction, Layout};
 use ratatui::style::{Color, Style};
+use ratatui::symbols::Marker;
 use ratatui::text::{Span, Text};
+use ratatui::widgets::canvas::{Canvas, Line as CanvasLine, Points};
 use ratatui::widgets::Paragraph;
 use ratatui::widgets::{Block, Borders, Wrap};
 use ratatui::Frame;
@@ -30,6 +37,225 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         .split(popup_layout[1])[1] // Return the middle chunk
 }
 
+/// The size in terminal cells of the CHIP-8 screen drawn at `scale_x` by `scale_y`.
+/// Each cell holds two pixels stacked vertically, see [`Marker::HalfBlock`].
+fn screen_size_in_cells(scale_x: u16, scale_y: u16) -> (u16, u16) {
+    let (width, height) = Emu::screen_size();
+    let width = u16::try_from(width).unwrap_or(u16::MAX);
+    let height = u16::try_from(height).unwrap_or(u16::MAX);
+    (
+        width.saturating_mul(scale_x),
+        height.saturating_mul(scale_y) / 2,
+    )
+}
+
+/// The largest canvas coordinates, x then y, of the CHIP-8 screen drawn at `scale_x` by
+/// `scale_y`. The canvas has one point for each half cell, starting from 0.
+#[allow(clippy::cast_precision_loss)]
+fn canvas_bounds(scale_x: usize, scale_y: usize) -> (f64, f64) {
+    let (screen_width, screen_height) = Emu::screen_size();
+    (
+        (screen_width * scale_x - 1) as f64,
+        (screen_height * scale_y - 1) as f64,
+    )
+}
+
+/// How many CHIP-8 pixels apart the debug gridlines are.
+const GRID_SPACING: usize = 8;
+
+/// The canvas coordinates of the debug gridlines for the CHIP-8 screen drawn at `scale_x` by
+/// `scale_y`, as (vertical line x positions, horizontal line y positions).
+/// A line is drawn along the left and top edge of every [`GRID_SPACING`]th pixel.
+#[allow(clippy::cast_precision_loss)]
+fn grid_lines(scale_x: usize, scale_y: usize) -> (Vec<f64>, Vec<f64>) {
+    let (screen_width, screen_height) = Emu::screen_size();
+    let xs = (GRID_SPACING..screen_width)
+        .step_by(GRID_SPACING)
+        .map(|x| (x * scale_x) as f64)
+        .collect();
+    // the canvas y axis points up, the CHIP-8 one points down
+    let ys = (GRID_SPACING..screen_height)
+        .step_by(GRID_SPACING)
+        .map(|y| ((screen_height - y) * scale_y - 1) as f64)
+        .collect();
+    (xs, ys)
+}
+
+/// The color lit pixels are drawn in on canvas row `row`, counted down from the top.
+/// With `scanlines`, every other row is dimmed to look like an old CRT.
+fn row_color(row: usize, scanlines: bool, theme: Theme) -> Color {
+    if scanlines && row % 2 == 1 {
+        theme.scanline_color()
+    } else {
+        theme.pixel_color()
+    }
+}
+
+/// The character showing a `top` and `bottom` pixel stacked in one terminal cell.
+fn half_block(top: bool, bottom: bool) -> char {
+    match (top, bottom) {
+        (true, true) => '█',
+        (true, false) => '▀',
+        (false, true) => '▄',
+        (false, false) => ' ',
+    }
+}
+
+/// The CHIP-8 screen after `transform`, as lines of [`half_block`] characters.
+/// Each pixel is `scale_x` characters wide and `scale_y` half characters tall.
+fn half_block_lines(
+    emu: &Emu,
+    transform: ScreenTransform,
+    scale_x: usize,
+    scale_y: usize,
+) -> Vec<String> {
+    let rows: Vec<&[bool]> = emu.screen_rows().collect();
+    let (screen_width, screen_height) = Emu::screen_size();
+    // every transform is its own inverse, so this finds the pixel shown at (x, y)
+    let pixel = |x: usize, y: usize| {
+        let (x, y) = transform.apply(x / scale_x, y / scale_y);
+        rows[y][x]
+    };
+    let height = screen_height * scale_y;
+    (0..height)
+        .step_by(2)
+        .map(|y| {
+            (0..screen_width * scale_x)
+                .map(|x| half_block(pixel(x, y), y + 1 < height && pixel(x, y + 1)))
+                .collect()
+        })
+        .collect()
+}
+
+/// Draws the CHIP-8 screen centered in `area`, each pixel `app.opts.scale` cells wide
+/// and `app.opts.scale_y` half cells tall.
+/// In debug mode, a grid and the screen's coordinates are drawn too.
+fn render_screen(f: &mut Frame<'_>, app: &App, area: Rect) {
+    let (scale_x, scale_y) = (app.opts.scale.max(1), app.opts.scale_y.max(1));
+    let (width, height) = screen_size_in_cells(scale_x, scale_y);
+    let area = Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width: width.min(area.width),
+        height: height.min(area.height),
+    };
+
+    let (scale_x, scale_y) = (usize::from(scale_x), usize::from(scale_y));
+    if app.opts.half_blocks {
+        let lines: Vec<Line<'_>> = half_block_lines(&app.emu, app.opts.transform, scale_x, scale_y)
+            .into_iter()
+            .map(Line::from)
+            .collect();
+        let screen = Paragraph::new(lines).style(Style::default().fg(app.opts.theme.pixel_color()));
+        f.render_widget(screen, area);
+        return;
+    }
+
+    let (screen_width, screen_height) = Emu::screen_size();
+    let (mut lit, mut dimmed) = (Vec::new(), Vec::new());
+    for (y, row) in app.emu.screen_rows().enumerate() {
+        for (x, _) in row.iter().enumerate().filter(|(_, &on)| on) {
+            let (x, y) = app.opts.transform.apply(x, y);
+            for dy in 0..scale_y {
+                let dimmed_row = row_color(y * scale_y + dy, app.opts.scanlines, app.opts.theme)
+                    != app.opts.theme.pixel_color();
+                let coords = if dimmed_row { &mut dimmed } else { &mut lit };
+                for dx in 0..scale_x {
+                    // the canvas y axis points up, the CHIP-8 one points down
+                    #[allow(clippy::cast_precision_loss)]
+                    coords.push((
+                        (x * scale_x + dx) as f64,
+                        ((screen_height - y) * scale_y - dy - 1) as f64,
+                    ));
+                }
+            }
+        }
+    }
+
+    let (max_x, max_y) = canvas_bounds(scale_x, scale_y);
+    let canvas = Canvas::default()
+        .marker(Marker::HalfBlock)
+        .x_bounds([0.0, max_x])
+        .y_bounds([0.0, max_y])
+        .paint(|ctx| {
+            if app.opts.debug {
+                let (xs, ys) = grid_lines(scale_x, scale_y);
+                for x in xs {
+                    ctx.draw(&CanvasLine::new(x, 0.0, x, max_y, Color::DarkGray));
+                }
+                for y in ys {
+                    ctx.draw(&CanvasLine::new(0.0, y, max_x, y, Color::DarkGray));
+                }
+                ctx.layer();
+            }
+
+            ctx.draw(&Points {
+                coords: &lit,
+                color: app.opts.theme.pixel_color(),
+            });
+            ctx.draw(&Points {
+                coords: &dimmed,
+                color: app.opts.theme.scanline_color(),
+            });
+
+            if app.opts.debug {
+                // label the first and last pixel of each axis
+                let label = Style::default().fg(Color::Yellow);
+                let last_x = (screen_width - 1).to_string();
+                #[allow(clippy::cast_precision_loss)]
+                let last_x_position = (screen_width * scale_x - last_x.len()) as f64;
+                ctx.print(0.0, max_y, Span::styled("0", label));
+                ctx.print(last_x_position, max_y, Span::styled(last_x, label));
+                ctx.print(0.0, 0.0, Span::styled((screen_height - 1).to_string(), label));
+            }
+        });
+
+    f.render_widget(canvas, area);
+}
+
+/// How many instructions the debug disassembly shows around the program counter.
+const DISASSEMBLY_LINES: usize = 10;
+
+/// The addresses of the `lines` instructions shown in the disassembly for `pc`, as a range to be
+/// stepped through 2 bytes at a time. The program counter is kept in the middle where possible,
+/// and the window is pushed back inside RAM near either end.
+fn disassembly_window(pc: usize, lines: usize) -> Range<usize> {
+    // opcodes are 2 bytes, counted from the program counter so odd addresses line up too
+    let available_after = (RAM_SIZE.saturating_sub(pc) / 2).max(1); // including the pc
+    let mut before = (lines / 2).min(pc / 2);
+    if before + available_after < lines {
+        before = (lines - available_after).min(pc / 2);
+    }
+    let start = pc - before * 2;
+    let end = (start + lines * 2).min(pc + available_after * 2);
+    start..end
+}
+
+/// Draws the instructions around the program counter, highlighting the one about to run.
+fn render_disassembly(f: &mut Frame<'_>, app: &App, area: Rect) {
+    let pc = app.emu.program_counter();
+    let lines: Vec<Line<'_>> = disassembly_window(usize::from(pc), DISASSEMBLY_LINES)
+        .step_by(2)
+        .filter_map(|address| u16::try_from(address).ok())
+        .map(|address| {
+            let text = match app.emu.opcode_at(address) {
+                Ok(opcode) => format!("{address:#05X}  {opcode:?}"),
+                Err(_) => format!("{address:#05X}  ???"),
+            };
+            let style = if address == pc {
+                Style::default().fg(Color::Black).bg(Color::Yellow)
+            } else {
+                Style::default()
+            };
+            Line::styled(text, style)
+        })
+        .collect();
+
+    let disassembly = Paragraph::new(lines)
+        .block(Block::default().title("Disassembly").borders(Borders::ALL));
+    f.render_widget(disassembly, area);
+}
+
 fn render_main_content(f: &mut Frame<'_>, app: &App, area: Rect) {
     // main block
     match app.state {
@@ -38,11 +264,18 @@ fn render_main_content(f: &mut Frame<'_>, app: &App, area: Rect) {
                 .borders(Borders::ALL)
                 .style(Style::default());
 
-            let info = Paragraph::new(Text::styled(
-                "Choocy is a TUI app for CHIP 8.",
-                Style::default().fg(Color::Blue),
-            ))
-            .block(info_block);
+            let style = Style::default().fg(Color::Blue);
+            let mut lines = vec![Line::styled("Choocy is a TUI app for CHIP 8.", style)];
+            if !app.rom_loaded {
+                lines.push(Line::styled(
+                    "Pass the path of a ROM to run it, or try a sample:",
+                    style,
+                ));
+                for (i, sample) in SAMPLE_ROMS.iter().enumerate() {
+                    lines.push(Line::styled(format!("  ({}) {}", i + 1, sample.name), style));
+                }
+            }
+            let info = Paragraph::new(lines).block(info_block);
 
             f.render_widget(info, area);
         }
@@ -50,16 +283,20 @@ fn render_main_content(f: &mut Frame<'_>, app: &App, area: Rect) {
             let running_block = Block::default()
                 .title("Emulator Running")
                 .borders(Borders::ALL)
-                .style(Style::default().bg(Color::Green));
+                .style(Style::default());
 
+            let inner = running_block.inner(area);
             f.render_widget(running_block, area);
-
-            // two parts
-            //
-            //  - figure out how to map our lib (display) -> tui
-            //  - figure out how to the fetech and execute loop -> tui
-            //
-            // now we need to figure out the logic needed to render the emulator screen
+            if app.opts.debug {
+                let chunks = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Min(1), Constraint::Length(36)])
+                    .split(inner);
+                render_screen(f, app, chunks[0]);
+                render_disassembly(f, app, chunks[1]);
+            } else {
+                render_screen(f, app, inner);
+            }
         }
         EmulateState::Paused => {
             let popup = Block::default()
@@ -79,13 +316,56 @@ fn render_main_content(f: &mut Frame<'_>, app: &App, area: Rect) {
 
             // TODO tell emulator to pause
         }
+        EmulateState::Remapping { selected, picked } => {
+            let popup = Block::default()
+                .title("Remap")
+                .borders(Borders::ALL)
+                .style(Style::default().bg(Color::DarkGray));
+
+            let prompt = if picked {
+                format!(
+                    "Press the keyboard key for CHIP-8 key {:X}",
+                    KEYPAD_LAYOUT[selected]
+                )
+            } else {
+                "Pick the CHIP-8 key to remap with the arrows and (enter), or type it (0-F)"
+                    .to_string()
+            };
+            let mut lines = vec![
+                Line::styled(prompt, Style::default().fg(Color::Green)),
+                Line::default(),
+            ];
+            // the keypad, with the selected key highlighted
+            for (row, keys) in KEYPAD_LAYOUT.chunks(KEYPAD_WIDTH).enumerate() {
+                let spans: Vec<Span<'_>> = keys
+                    .iter()
+                    .enumerate()
+                    .map(|(col, key)| {
+                        let style = if row * KEYPAD_WIDTH + col == selected {
+                            Style::default().fg(Color::Black).bg(Color::Yellow)
+                        } else {
+                            Style::default()
+                        };
+                        Span::styled(format!(" {key:X} "), style)
+                    })
+                    .collect();
+                lines.push(Line::from(spans));
+            }
+            let remap_block = Paragraph::new(lines)
+                .block(popup)
+                .wrap(Wrap { trim: false });
+
+            let area = centered_rect(60, 50, f.size());
+            f.render_widget(remap_block, area);
+        }
         // I assume we should map libraries errors here, and also if the emulator itself has an error
         EmulateState::Error => {
             let error_block = Block::default()
                 .borders(Borders::ALL)
                 .style(Style::default());
 
-            let error = Paragraph::new(Text::styled("IDK mate", Style::default().fg(Color::Red)))
+            let message = app.error.as_deref().unwrap_or("IDK mate");
+            let error = Paragraph::new(Text::styled(message, Style::default().fg(Color::Red)))
                 .block(error_block);
 
             f.render_widget(error, area);
@@ -93,34 +373,15 @@ fn render_main_content(f: &mut Frame<'_>, app: &App, area: Rect) {
     }
 }
 
-pub fn ui(f: &mut Frame<'_>, app: &App) {
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(3), // header
-            Constraint::Min(1),    // main content
-            Constraint::Length(3), // footer
-        ])
-        .split(f.size());
-
-    let title_block = Block::default()
-        .borders(Borders::ALL)
-        .style(Style::default());
-
-    let title = Paragraph::new(Text::styled("Choocy", Style::default().fg(Color::Green)))
-        .block(title_block);
-
-    f.render_widget(title, chunks[0]);
-
-    render_main_content(f, app, chunks[1]);
-
-    // footer
-    let current_navigation_text = vec![
+/// The left half of the footer: the current screen, whether the emulator is running,
+/// and any toast or beep.
+fn navigation_spans(app: &App) -> Vec<Span<'_>> {
+    let mut current_navigation_text = vec![
         // The first half of the text
         match app.current_screen {
             CurrentScreen::Home => Span::styled("Home", Style::default().fg(Color::Green)),
             CurrentScreen::Emulate => Span::styled("Emulate", Style::default().fg(Color::Yellow)),
-            _ => todo!(),
+            CurrentScreen::Rom => todo!(),
         }
         .clone(),
         // A white divider bar to separate the two sections
@@ -131,15 +392,53 @@ pub fn ui(f: &mut Frame<'_>, app: &App) {
                 EmulateState::Off => {
                     Span::styled("Not Running", Style::default().fg(Color::DarkGray))
                 }
+                EmulateState::Running if app.turbo => {
+                    Span::styled("Running (turbo)", Style::default().fg(Color::Magenta))
+                }
                 EmulateState::Running => Span::styled("Running", Style::default().fg(Color::Green)),
                 EmulateState::Paused => {
                     Span::styled("Paused", Style::default().fg(Color::LightRed))
                 }
+                EmulateState::Remapping { .. } => {
+                    Span::styled("Remapping", Style::default().fg(Color::LightRed))
+                }
                 EmulateState::Error => Span::styled("Error", Style::default().fg(Color::Red)),
             }
         },
     ];
+    if let Some(toast) = app.toast() {
+        current_navigation_text.push(Span::styled(" | ", Style::default().fg(Color::White)));
+        current_navigation_text.push(Span::styled(toast, Style::default().fg(Color::Cyan)));
+    }
+    if app.emu.is_beeping() {
+        current_navigation_text.push(Span::styled(" ♪", Style::default().fg(Color::Yellow)));
+    }
+    current_navigation_text
+}
+
+pub fn ui(f: &mut Frame<'_>, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3), // header
+            Constraint::Min(1),    // main content
+            Constraint::Length(3), // footer
+        ])
+        .split(f.size());
+
+    let title_block = Block::default()
+        .borders(Borders::ALL)
+        .style(Style::default());
+
+    let title = Paragraph::new(Text::styled("Choocy", Style::default().fg(Color::Green)))
+        .block(title_block);
+
+    f.render_widget(title, chunks[0]);
+
+    render_main_content(f, app, chunks[1]);
 
+    // footer
+    let current_navigation_text = navigation_spans(app);
     let mode_footer = Paragraph::new(Line::from(current_navigation_text))
         .block(Block::default().borders(Borders::ALL));
 
@@ -147,10 +446,28 @@ pub fn ui(f: &mut Frame<'_>, app: &App) {
         match app.current_screen {
             CurrentScreen::Home => {
                 // TODO: should we add a load, save, or configure option here?
-                Span::styled("(q) to quit / (r) to run", Style::default().fg(Color::Red))
+                Span::styled(
+                    "(q) to quit / (r) to run / (1-2) for a sample",
+                    Style::default().fg(Color::Red),
+                )
             }
-            CurrentScreen::Emulate => todo!(),
-            _ => todo!(),
+            CurrentScreen::Emulate => match app.state {
+                EmulateState::Paused => {
+                    Span::styled("(q) to quit / (r) to resume / (m) to remap a key / (ctrl+r) to reset", Style::default().fg(Color::Red))
+                }
+                EmulateState::Remapping { .. } => {
+                    Span::styled("(arrows) to move / (enter) to pick / (backspace) default keys / (esc) to cancel", Style::default().fg(Color::Red))
+                }
+                EmulateState::Error => Span::styled("(q) to quit", Style::default().fg(Color::Red)),
+                _ => {
+                    let turbo = if app.key_releases { "hold (tab) for turbo" } else { "(tab) toggles turbo" };
+                    Span::styled(
+                        format!("(esc) to pause / {turbo} / (shift+1-3) speed / (ctrl+s) scanlines / (ctrl+r) to reset"),
+                        Style::default().fg(Color::Red),
+                    )
+                }
+            },
+            CurrentScreen::Rom => todo!(),
         }
     };
 
@@ -164,3 +481,100 @@ pub fn ui(f: &mut Frame<'_>, app: &App) {
     f.render_widget(mode_footer, footer_chunks[0]);
     f.render_widget(key_notes_footer, footer_chunks[1]);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_navigation_spans_beep() {
+        let has_note = |app: &App| {
+            navigation_spans(app)
+                .iter()
+                .any(|span| span.content.contains('♪'))
+        };
+
+        let mut app = App::new(Config::default(), Config::default());
+        // beep for 2 frames, then jump to itself
+        app.load_rom(&[0x60, 0x02, 0xF0, 0x18, 0x12, 0x04]).unwrap();
+        assert!(!has_note(&app));
+
+        app.run_frame();
+        assert!(has_note(&app));
+
+        app.run_frame();
+        assert!(!has_note(&app));
+    }
+
+    #[test]
+    fn test_disassembly_window() {
+        // centered on the program counter
+        assert_eq!(disassembly_window(0x200, 10), 0x1F6..0x20A);
+        // odd addresses keep their alignment
+        assert_eq!(disassembly_window(0x201, 10), 0x1F7..0x20B);
+        // pushed back inside RAM at either end
+        assert_eq!(disassembly_window(0x002, 10), 0x000..0x014);
+        assert_eq!(disassembly_window(RAM_SIZE - 2, 10), RAM_SIZE - 20..RAM_SIZE);
+    }
+
+    #[test]
+    fn test_row_color() {
+        let theme = Theme::default();
+        assert_eq!(row_color(0, false, theme), Color::White);
+        assert_eq!(row_color(1, false, theme), Color::White);
+
+        assert_eq!(row_color(0, true, theme), Color::White);
+        assert_eq!(row_color(1, true, theme), Color::Gray);
+        assert_eq!(row_color(2, true, theme), Color::White);
+
+        assert_eq!(row_color(1, true, Theme::Green), Color::Green);
+    }
+
+    #[test]
+    fn test_half_block() {
+        assert_eq!(half_block(true, true), '█');
+        assert_eq!(half_block(true, false), '▀');
+        assert_eq!(half_block(false, true), '▄');
+        assert_eq!(half_block(false, false), ' ');
+
+        let mut emu = Emu::new();
+        // draw the 1 font sprite in the top left corner
+        emu.load_rom(&[0x61, 0x01, 0xF1, 0x29, 0xD0, 0x05]).unwrap();
+        for _ in 0..3 {
+            emu.cycle().unwrap();
+        }
+        let lines = half_block_lines(&emu, ScreenTransform::None, 1, 1);
+        assert_eq!(lines.len(), SCREEN_HEIGHT / 2);
+        // the rows of the 1 are ..#., .##., ..#., ..#., .###
+        assert!(lines[0].starts_with(" ▄█ "));
+        assert!(lines[1].starts_with("  █ "));
+        assert!(lines[2].starts_with(" ▀▀▀"));
+        assert_eq!(lines[0].chars().count(), SCREEN_WIDTH);
+    }
+
+    #[test]
+    fn test_grid_lines() {
+        let (xs, ys) = grid_lines(1, 1);
+        assert_eq!(xs, vec![8.0, 16.0, 24.0, 32.0, 40.0, 48.0, 56.0]);
+        // pixel rows 8, 16 and 24, counted up from the bottom of the canvas
+        assert_eq!(ys, vec![23.0, 15.0, 7.0]);
+
+        let (xs, ys) = grid_lines(2, 2);
+        assert_eq!(xs.first(), Some(&16.0));
+        assert_eq!(ys.first(), Some(&47.0));
+
+        let (xs, ys) = grid_lines(2, 1);
+        assert_eq!(xs.first(), Some(&16.0));
+        assert_eq!(ys.first(), Some(&23.0));
+    }
+
+    #[test]
+    fn test_canvas_bounds() {
+        assert_eq!(canvas_bounds(1, 1), (63.0, 31.0));
+        // twice as wide, as tall as before
+        assert_eq!(canvas_bounds(2, 1), (127.0, 31.0));
+        assert_eq!(screen_size_in_cells(2, 1), (128, 16));
+        assert_eq!(screen_size_in_cells(3, 2), (192, 32));
+    }
+}
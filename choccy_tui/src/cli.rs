@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use choccy_chip::prelude::ScreenTransform;
+
+use crate::choocy::{Speed, Theme};
+use crate::config::Config;
+
+/// A TUI for the Choccy Chip CHIP-8 emulator.
+/// Any option given here overrides the saved config file for this run only.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// The ROM to run
+    pub rom: Option<PathBuf>,
+    /// How fast the emulator runs: slow, normal or fast
+    #[arg(short, long)]
+    pub speed: Option<Speed>,
+    /// How many terminal cells wide each CHIP-8 pixel is
+    #[arg(long)]
+    pub scale: Option<u16>,
+    /// How many half cells tall each CHIP-8 pixel is, the same as --scale if not given
+    #[arg(long)]
+    pub scale_y: Option<u16>,
+    /// The most instructions to run in a single frame, even with turbo
+    #[arg(long)]
+    pub budget: Option<usize>,
+    /// Don't ring the terminal bell when the game beeps
+    #[arg(long)]
+    pub no_bell: bool,
+    /// Show debugging information
+    #[arg(short, long)]
+    pub debug: bool,
+    /// Dim every other row of the screen, like an old CRT
+    #[arg(long)]
+    pub scanlines: bool,
+    /// Draw the screen with half-block characters instead of a canvas
+    #[arg(long)]
+    pub half_blocks: bool,
+    /// Mirror or rotate the screen: none, flip-h, flip-v or rotate180
+    #[arg(long)]
+    pub transform: Option<ScreenTransform>,
+    /// The colors the screen is drawn in: white, green or amber
+    #[arg(long)]
+    pub theme: Option<Theme>,
+}
+
+impl Cli {
+    /// The settings given on the command line, to be layered over the config file.
+    pub fn overrides(&self) -> Config {
+        Config {
+            speed: self.speed,
+            scale: self.scale,
+            scale_y: self.scale_y,
+            budget: self.budget,
+            bell: self.no_bell.then_some(false),
+            beep_cooldown: None,
+            debug: self.debug.then_some(true),
+            scanlines: self.scanlines.then_some(true),
+            half_blocks: self.half_blocks.then_some(true),
+            transform: self.transform,
+            theme: self.theme,
+            quirks: None,
+            keymap: None,
+        }
+    }
+}
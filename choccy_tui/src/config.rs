@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
+
+use color_eyre::eyre::WrapErr;
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use choccy_chip::prelude::{Quirks, ScreenTransform};
+
+use crate::choocy::{Speed, Theme, BEEP_COOLDOWN, INSTRUCTION_BUDGET, SCALE_FACTOR};
+
+/// User preferences, saved to `config.toml` in the user's config directory.
+///
+/// Every field is optional so the config file and the command line only need to
+/// mention the settings they change. The effective settings are built by layering
+/// [`Config::defaults`], then the config file, then the command line with [`Config::merge`].
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub speed: Option<Speed>,
+    pub scale: Option<u16>,
+    /// how many half cells tall a pixel is, the same as `scale` when unset
+    pub scale_y: Option<u16>,
+    /// the most instructions run in a frame, whatever the speed or turbo
+    pub budget: Option<usize>,
+    /// ring the terminal bell while there is no audio to play
+    pub bell: Option<bool>,
+    /// the fewest frames between two rings of the bell
+    pub beep_cooldown: Option<u64>,
+    pub debug: Option<bool>,
+    /// dim every other row of the screen, like an old CRT
+    pub scanlines: Option<bool>,
+    /// draw the screen as half-block characters rather than on a canvas
+    pub half_blocks: Option<bool>,
+    /// mirror or rotate the screen
+    pub transform: Option<ScreenTransform>,
+    /// the colors the screen is drawn in
+    pub theme: Option<Theme>,
+    /// the interpreter behaviours to follow, rather than the emulator's defaults
+    pub quirks: Option<Quirks>,
+    /// keyboard input -> CHIP-8 key
+    pub keymap: Option<HashMap<String, usize>>,
+}
+
+impl Config {
+    /// The built-in settings, used for anything the config file and command line leave unset.
+    pub fn defaults() -> Self {
+        Self {
+            speed: Some(Speed::default()),
+            scale: Some(SCALE_FACTOR),
+            scale_y: None, // follows scale
+            budget: Some(INSTRUCTION_BUDGET),
+            bell: Some(true),
+            beep_cooldown: Some(BEEP_COOLDOWN),
+            debug: Some(false),
+            scanlines: Some(false),
+            half_blocks: Some(false),
+            transform: Some(ScreenTransform::None),
+            theme: Some(Theme::default()),
+            quirks: None, // left to the emulator
+            keymap: None,
+        }
+    }
+
+    /// Layers `other` on top of `self`: a setting in `other` wins over the same setting in `self`.
+    #[must_use]
+    pub fn merge(self, other: Config) -> Config {
+        Self {
+            speed: other.speed.or(self.speed),
+            scale: other.scale.or(self.scale),
+            scale_y: other.scale_y.or(self.scale_y),
+            budget: other.budget.or(self.budget),
+            bell: other.bell.or(self.bell),
+            beep_cooldown: other.beep_cooldown.or(self.beep_cooldown),
+            debug: other.debug.or(self.debug),
+            scanlines: other.scanlines.or(self.scanlines),
+            half_blocks: other.half_blocks.or(self.half_blocks),
+            transform: other.transform.or(self.transform),
+            theme: other.theme.or(self.theme),
+            quirks: other.quirks.or(self.quirks),
+            keymap: other.keymap.or(self.keymap),
+        }
+    }
+
+    /// `$XDG_CONFIG_HOME/choccy/config.toml`, falling back to `~/.config/choccy/config.toml`.
+    pub fn path() -> Option<PathBuf> {
+        let config_dir = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_dir.join("choccy").join("config.toml"))
+    }
+
+    /// Loads the user's config file. A missing file is not an error, it gives an empty config.
+    pub fn load() -> Result<Self> {
+        match Self::path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(contents) => toml::from_str(&contents)
+                .wrap_err_with(|| format!("failed to parse config file {}", path.display())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => {
+                Err(err).wrap_err_with(|| format!("failed to read config file {}", path.display()))
+            }
+        }
+    }
+
+    /// Writes the config to `path`, creating the directory if needed.
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .wrap_err_with(|| format!("failed to create config directory {}", dir.display()))?;
+        }
+        let contents = toml::to_string_pretty(self).wrap_err("failed to serialize config")?;
+        fs::write(path, contents)
+            .wrap_err_with(|| format!("failed to write config file {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_precedence() {
+        let file = Config {
+            speed: Some(Speed::Fast),
+            scale: Some(3),
+            ..Config::default()
+        };
+        let cli = Config {
+            speed: Some(Speed::Slow),
+            ..Config::default()
+        };
+
+        let settings = Config::defaults().merge(file).merge(cli);
+
+        assert_eq!(settings.speed, Some(Speed::Slow)); // cli beats config
+        assert_eq!(settings.scale, Some(3)); // config beats defaults
+        assert_eq!(settings.debug, Some(false)); // defaults fill the rest
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = env::temp_dir()
+            .join(format!("choccy-test-{}", std::process::id()))
+            .join("config.toml");
+        let config = Config {
+            speed: Some(Speed::Fast),
+            keymap: Some(HashMap::from([("k".to_string(), 5)])),
+            transform: Some(ScreenTransform::FlipH),
+            theme: Some(Theme::Amber),
+            quirks: Some(Quirks {
+                clip: true,
+                ..Quirks::default()
+            }),
+            ..Config::default()
+        };
+
+        config.save_to(&path).unwrap();
+        let loaded = Config::load_from(&path).unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert_eq!(loaded, config);
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        let path = env::temp_dir().join("choccy-does-not-exist").join("config.toml");
+        assert_eq!(Config::load_from(&path).unwrap(), Config::default());
+    }
+}
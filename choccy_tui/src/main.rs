@@ -3,9 +3,17 @@
 
 //! Choccy TUI is a TUI for the Choccy Chip CHIP-8 emulator.
 
+use std::fs;
+
+use clap::Parser;
+use color_eyre::eyre::WrapErr;
 use color_eyre::Result;
 /// Where the choocy app is defined. Includes the `App` struct and the `CurrentScreen` enum.
 mod choocy;
+/// Command line arguments for the TUI.
+mod cli;
+/// User preferences that persist between runs.
+mod config;
 /// Error handling for the TUI
 mod errors;
 /// The TUI module, where the `TUI` is initialized.
@@ -13,13 +21,21 @@ mod tui;
 
 fn main() -> Result<()> {
     errors::install_hooks()?; // error handling
+    let cli = cli::Cli::parse();
+    let config = config::Config::load()?;
+    let mut app = choocy::App::new(config, cli.overrides()).saving_to(config::Config::path());
+    if let Some(path) = &cli.rom {
+        let rom = fs::read(path).wrap_err_with(|| format!("failed to read {}", path.display()))?;
+        app.load_rom(&rom)?;
+    }
+
     let mut terminal = tui::init()?;
+    // restores the terminal however we exit, panics are handled by the hooks
+    let _guard = tui::TerminalGuard::new();
 
     // everything is handled in the app module
     // edit this!
-    choocy::App::new().run(&mut terminal)?;
-
+    app.run(&mut terminal)?;
 
-    tui::restore()?;
     Ok(())
 }
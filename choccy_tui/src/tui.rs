@@ -1,18 +1,102 @@
-use std::io::{self, stdout, Stdout};
-use crossterm::{execute, terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode}};
+use std::io::{self, stdout, Stdout, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use crossterm::{
+    event::{KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags},
+    execute,
+    terminal::{
+        disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
+};
 use ratatui::prelude::*;
 
 /// A type alias for the terminal type used in this application
 pub type Tui = Terminal<CrosstermBackend<Stdout>>;
 
+/// Whether we asked the terminal for key release events, and so need to undo it on restore
+static KEYBOARD_ENHANCED: AtomicBool = AtomicBool::new(false);
+
 pub fn init() -> io::Result<Tui> {
     execute!(stdout(), EnterAlternateScreen)?;
     enable_raw_mode()?;
+    // ask for key release events, so keys can be held down
+    if supports_keyboard_enhancement().unwrap_or(false) {
+        execute!(
+            stdout(),
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+        )?;
+        KEYBOARD_ENHANCED.store(true, Ordering::Relaxed);
+    }
     Terminal::new(CrosstermBackend::new(stdout()))
 }
 
+/// Whether the terminal reports key release events, see [`init`].
+/// Without them, a key cannot be told apart from a key that is held down.
+pub fn key_releases_reported() -> bool {
+    KEYBOARD_ENHANCED.load(Ordering::Relaxed)
+}
+
 pub fn restore() -> io::Result<()> {
+    if KEYBOARD_ENHANCED.swap(false, Ordering::Relaxed) {
+        execute!(stdout(), PopKeyboardEnhancementFlags)?;
+    }
     execute!(stdout(), LeaveAlternateScreen)?;
     disable_raw_mode()?;
     Ok(())
 }
+
+/// Restores the terminal when dropped, so it is restored however `main` exits,
+/// including returning an error early
+#[derive(Debug)]
+pub struct TerminalGuard {
+    restore: fn() -> io::Result<()>,
+}
+
+impl TerminalGuard {
+    /// A guard that calls [`restore`] when dropped
+    pub fn new() -> Self {
+        Self::with_restore(restore)
+    }
+
+    /// A guard that calls `restore` when dropped
+    pub fn with_restore(restore: fn() -> io::Result<()>) -> Self {
+        Self { restore }
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        // nothing more we can do if restoring fails while shutting down
+        let _ = (self.restore)();
+    }
+}
+
+/// Rings the terminal bell, which is how we play a sound until there is audio
+pub fn bell() -> io::Result<()> {
+    let mut stdout = stdout();
+    stdout.write_all(b"\x07")?;
+    stdout.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    static RESTORES: AtomicUsize = AtomicUsize::new(0);
+
+    #[allow(clippy::unnecessary_wraps)] // it has to match `restore`
+    fn count_restore() -> io::Result<()> {
+        RESTORES.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    #[test]
+    fn test_guard_restores_on_drop() {
+        let guard = TerminalGuard::with_restore(count_restore);
+        assert_eq!(RESTORES.load(Ordering::Relaxed), 0);
+
+        drop(guard);
+        assert_eq!(RESTORES.load(Ordering::Relaxed), 1);
+    }
+}